@@ -1,9 +1,10 @@
-use std::fs::read_to_string;
+use std::{fmt::Debug, fs::read_to_string};
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
-use aoc2023::Runner;
+use aoc2023::{parsers::number_list, utils::bytes, Runner};
 use pprof::{criterion::Output, flamegraph::Options};
+use winnow::Parser;
 
 macro_rules! days {
     () => {};
@@ -20,6 +21,14 @@ macro_rules! days {
             let input = $day::Day::get_input(&input).unwrap();
             group.bench_function("part1", |b| b.iter(|| black_box($day::Day::part1(&input))));
             group.bench_function("part2", |b| b.iter(|| black_box($day::Day::part2(&input))));
+            for variant in $day::Day::variants() {
+                group.bench_function(format!("{}/part1", variant.name), |b| {
+                    b.iter(|| black_box((variant.part1)(&input)))
+                });
+                group.bench_function(format!("{}/part2", variant.name), |b| {
+                    b.iter(|| black_box((variant.part2)(&input)))
+                });
+            }
             group.finish();
         }
     };
@@ -32,18 +41,100 @@ macro_rules! days {
 macro_rules! benches {
     ($day:ident, $($days:ident),* $(,)?) => {
         days! { $day, $($days),* }
+
+        fn end_to_end(c: &mut Criterion) {
+            let mut group = c.benchmark_group("end_to_end");
+            group.bench_function(stringify!($day), |b| {
+                b.iter(|| black_box(pipeline::<_, _, $day::Day>()))
+            });
+            $(
+                group.bench_function(stringify!($days), |b| {
+                    b.iter(|| black_box(pipeline::<_, _, $days::Day>()))
+                });
+            )*
+            group.finish();
+        }
+
+        fn year(c: &mut Criterion) {
+            let mut group = c.benchmark_group("year");
+            group.bench_function("all", |b| {
+                b.iter(|| {
+                    black_box(pipeline::<_, _, $day::Day>());
+                    $(black_box(pipeline::<_, _, $days::Day>());)*
+                })
+            });
+            group.finish();
+        }
+
         criterion_group!(
             name = benches;
             config = custom();
             targets = $day,
-                $($days),*
+                $($days,)*
+                end_to_end,
+                year,
+                parsing
         );
 
         criterion_main!(benches);
     };
 }
 
-benches!(day01, day02, day03, day04, day05, day06, day07, day08, );
+benches!(day01, day02, day03, day04, day05, day06, day07, day08,);
+
+/// Everything `Runner::run` does for a day, minus the logging: read the input file, parse it
+/// and solve both parts.
+fn pipeline<Part1, Part2, R>() -> (Part1, Part2)
+where
+    Part1: Debug + PartialEq,
+    Part2: Debug + PartialEq,
+    R: Runner<Part1, Part2>,
+{
+    let input = read_to_string(format!("input/2023/day{:02}.txt", R::day())).unwrap();
+    let input = R::get_input(&input).unwrap();
+    (R::part1(&input).unwrap(), R::part2(&input).unwrap())
+}
+
+/// The winnow combinators against the byte scanners, summing every number in a table.
+fn parsing(c: &mut Criterion) {
+    let input = (0..1000usize)
+        .map(|row| {
+            (0..20usize)
+                .map(|col| format!("{:>4}", (row * 31 + col * 17) % 10_000))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut group = c.benchmark_group("parsing");
+    group.bench_function("winnow", |b| {
+        b.iter(|| {
+            black_box(
+                input
+                    .lines()
+                    .map(|line| {
+                        number_list::<usize>
+                            .parse(line.trim())
+                            .unwrap()
+                            .into_iter()
+                            .sum::<usize>()
+                    })
+                    .sum::<usize>(),
+            )
+        })
+    });
+    group.bench_function("bytes", |b| {
+        b.iter(|| {
+            black_box(
+                bytes::lines(input.as_bytes())
+                    .map(|line| bytes::numbers(line).sum::<usize>())
+                    .sum::<usize>(),
+            )
+        })
+    });
+    group.finish();
+}
 
 fn custom() -> Criterion {
     let mut options = Options::default();
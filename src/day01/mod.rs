@@ -1,8 +1,10 @@
-use std::collections::HashMap;
-
 use color_eyre::Result;
+use once_cell::sync::Lazy;
 
-use crate::Runner;
+use crate::{
+    utils::{bytes::lines, matcher::Matcher},
+    Runner,
+};
 
 pub struct Day;
 
@@ -31,47 +33,42 @@ impl Runner for Day {
     }
 
     fn part2(input: &Self::Input<'_>) -> Result<usize> {
-        let mut map = HashMap::new();
-        map.insert("one", 1);
-        map.insert("two", 2);
-        map.insert("three", 3);
-        map.insert("four", 4);
-        map.insert("five", 5);
-        map.insert("six", 6);
-        map.insert("seven", 7);
-        map.insert("eight", 8);
-        map.insert("nine", 9);
-        map.insert("1", 1);
-        map.insert("2", 2);
-        map.insert("3", 3);
-        map.insert("4", 4);
-        map.insert("5", 5);
-        map.insert("6", 6);
-        map.insert("7", 7);
-        map.insert("8", 8);
-        map.insert("9", 9);
-        Ok({
-            input
-                .lines()
-                .map(|line| {
-                    let min = map
-                        .keys()
-                        .filter_map(|k| line.find(k).map(|idx| (idx, map[k])))
-                        .min_by_key(|t| t.0)
-                        .unwrap();
-                    let max = map
-                        .keys()
-                        .filter_map(|k| line.rfind(k).map(|idx| (idx, map[k])))
-                        .max_by_key(|t| t.0)
-                        .unwrap();
-                    (min.1, max.1)
-                })
-                .map(|(ten, one)| 10 * ten + one)
-                .sum()
-        })
+        let (forward, backward) = &*DIGITS;
+        Ok(lines(input.as_bytes())
+            .map(|line| {
+                let first = forward.find(line.iter().copied());
+                let last = backward.find(line.iter().rev().copied());
+                first
+                    .zip(last)
+                    .map_or(0, |((_, _, ten), (_, _, one))| 10 * ten + one)
+            })
+            .sum())
     }
 }
 
+/// Digits and their names, matched forwards for the first digit on a line and with every pattern
+/// reversed for the last.
+static DIGITS: Lazy<(Matcher<usize>, Matcher<usize>)> = Lazy::new(|| {
+    let patterns = || {
+        [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ]
+        .into_iter()
+        .zip(1..)
+        .chain(
+            ["1", "2", "3", "4", "5", "6", "7", "8", "9"]
+                .into_iter()
+                .zip(1..),
+        )
+    };
+    (
+        Matcher::new(patterns()),
+        Matcher::new(
+            patterns().map(|(word, digit)| (word.bytes().rev().collect::<Vec<_>>(), digit)),
+        ),
+    )
+});
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,4 +97,13 @@ zoneight234
         part1 = 54644;
         part2 = 53348;
     }
+
+    #[test]
+    fn overlapping_words() -> Result<()> {
+        assert_eq!(82, Day::part2(&"eightwo")?);
+        assert_eq!(21, Day::part2(&"twone")?);
+        assert_eq!(77, Day::part2(&"seven")?);
+        assert_eq!(0, Day::part2(&"abc")?);
+        Ok(())
+    }
 }
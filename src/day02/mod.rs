@@ -1,107 +1,136 @@
 use std::collections::BTreeMap;
 
 use color_eyre::Result;
-use itertools::any;
 use winnow::{
-    ascii::digit1,
-    combinator::{alt, not, preceded, repeat, separated},
-    token::take_while,
+    ascii::{alpha1, line_ending},
+    combinator::{preceded, separated, separated_pair},
     PResult, Parser,
 };
 
-use crate::Runner;
+use crate::{
+    parsers::{number, parse_input},
+    Runner,
+};
 
 pub struct Day;
 
-#[derive(Debug, Default)]
-pub struct Round {
-    red: usize,
-    green: usize,
-    blue: usize,
+/// How many cubes of each color: either what was drawn in one round, or what a bag holds.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Round<'a>(BTreeMap<&'a str, usize>);
+
+impl<'a> Round<'a> {
+    /// The bag part 1 asks about.
+    pub fn puzzle_bag() -> Self {
+        Self::from_iter([("red", 12), ("green", 13), ("blue", 14)])
+    }
+
+    pub fn count(&self, color: &str) -> usize {
+        self.0.get(color).copied().unwrap_or(0)
+    }
+
+    /// Whether `bag` has enough of every color to have drawn this.
+    pub fn fits_in(&self, bag: &Round) -> bool {
+        self.0
+            .iter()
+            .all(|(color, &count)| count <= bag.count(color))
+    }
+
+    /// The least of each color that could have drawn both `self` and `other`.
+    pub fn max(mut self, other: &Round<'a>) -> Self {
+        for (&color, &count) in &other.0 {
+            let entry = self.0.entry(color).or_default();
+            *entry = (*entry).max(count);
+        }
+        self
+    }
+
+    /// The red, green and blue counts multiplied together.
+    pub fn power(&self) -> usize {
+        ["red", "green", "blue"]
+            .into_iter()
+            .map(|color| self.count(color))
+            .product()
+    }
+}
+
+impl<'a> FromIterator<(&'a str, usize)> for Round<'a> {
+    fn from_iter<T: IntoIterator<Item = (&'a str, usize)>>(iter: T) -> Self {
+        let mut round = Self::default();
+        iter.into_iter()
+            .for_each(|(color, count)| *round.0.entry(color).or_default() += count);
+        round
+    }
 }
 
-fn round(input: &mut &str) -> PResult<Round> {
-    let colors: Vec<(usize, char, &str)> = separated(
-        1..,
-        (
-            digit1.try_map(str::parse),
-            ' ',
-            alt(("green", "red", "blue")),
-        ),
-        ", ",
-    )
-    .parse_next(input)?;
-    let color = colors
-        .iter()
-        .fold(Round::default(), |round, (num, _, color)| match *color {
-            "red" => Round { red: *num, ..round },
-            "green" => Round {
-                green: *num,
-                ..round
-            },
-            "blue" => Round {
-                blue: *num,
-                ..round
-            },
-            _ => unreachable!(),
-        });
-    Ok(color)
+/// Counts and colors, like `3 blue, 4 red`. Any word is a color.
+fn round<'a>(input: &mut &'a str) -> PResult<Round<'a>> {
+    let colors: Vec<(usize, &str)> =
+        separated(1.., separated_pair(number, ' ', alpha1), ", ").parse_next(input)?;
+    Ok(colors
+        .into_iter()
+        .map(|(count, color)| (color, count))
+        .collect())
 }
 
-fn game(input: &mut &str) -> PResult<(usize, Vec<Round>)> {
-    let game: usize = preceded("Game ", digit1.try_map(str::parse)).parse_next(input)?;
+fn game<'a>(input: &mut &'a str) -> PResult<(usize, Vec<Round<'a>>)> {
+    let game = preceded("Game ", number).parse_next(input)?;
     let _ = ": ".parse_next(input)?;
-    let mut rounds: Vec<&str> =
-        separated(1.., take_while(1.., |c| c != ';').recognize(), "; ").parse_next(input)?;
-    let rounds = rounds
-        .iter_mut()
-        .map(|r| round(r).unwrap())
-        .collect::<Vec<_>>();
+    let rounds = separated(1.., round, "; ").parse_next(input)?;
     Ok((game, rounds))
 }
 
+/// A bag written the same way as a round, e.g. `12 red, 13 green, 14 blue`.
+pub fn parse_bag(input: &str) -> Result<Round<'_>> {
+    Ok(parse_input(round, input.trim())?)
+}
+
+/// The ids of the games that could have been played with `bag`.
+pub fn possible_games<'a>(
+    games: &'a BTreeMap<usize, Vec<Round>>,
+    bag: &'a Round,
+) -> impl Iterator<Item = usize> + 'a {
+    games
+        .iter()
+        .filter(|(_, rounds)| rounds.iter().all(|round| round.fits_in(bag)))
+        .map(|(game, _)| *game)
+}
+
 impl Runner for Day {
-    type Input<'input> = BTreeMap<usize, Vec<Round>>;
+    type Input<'input> = BTreeMap<usize, Vec<Round<'input>>>;
 
     fn day() -> usize {
         2
     }
 
     fn get_input(input: &str) -> Result<Self::Input<'_>> {
-        Ok(input
-            .lines()
-            .map(|mut line| game.parse_next(&mut line).unwrap())
-            .collect())
+        let games: Vec<_> = parse_input(separated(1.., game, line_ending), input)?;
+        Ok(games.into_iter().collect())
     }
 
     fn part1(input: &Self::Input<'_>) -> Result<usize> {
-        let red = 12;
-        let green = 13;
-        let blue = 14;
-        Ok(input
-            .iter()
-            .filter(|(_, rounds)| {
-                !any(rounds.iter(), |r| {
-                    r.red > red || r.green > green || r.blue > blue
-                })
-            })
-            // .map(|g| dbg!(g))
-            .map(|(game, _)| *game)
-            .sum::<usize>())
+        Ok(possible_games(input, &Round::puzzle_bag()).sum())
     }
 
     fn part2(input: &Self::Input<'_>) -> Result<usize> {
-        Ok(input.iter().map(|(_, rounds)| power(rounds)).sum::<usize>())
+        Ok(input
+            .values()
+            .map(|rounds| minimum_bag(rounds).power())
+            .sum())
+    }
+
+    fn explain(input: &Self::Input<'_>, query: &str) -> Result<String> {
+        let bag = parse_bag(query)?;
+        Ok(format!(
+            "Possible with {:?}: {:?}",
+            bag.0,
+            possible_games(input, &bag).collect::<Vec<_>>()
+        ))
     }
 }
 
-fn power(input: &[Round]) -> usize {
-    let round = input.iter().fold(Round::default(), |acc, r| Round {
-        red: acc.red.max(r.red),
-        green: acc.green.max(r.green),
-        blue: acc.blue.max(r.blue),
-    });
-    round.red * round.green * round.blue
+/// The smallest bag every round of a game could have come from.
+fn minimum_bag<'a>(rounds: &[Round<'a>]) -> Round<'a> {
+    rounds.iter().fold(Round::default(), Round::max)
 }
 
 #[cfg(test)]
@@ -109,14 +138,16 @@ mod tests {
     use super::*;
     use crate::helpers::{prod_case, sample_case};
 
+    const SAMPLE: &str = "\
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\n\
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\n\
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\n\
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\n\
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
     sample_case! {
         sample1 =>
-            input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
-";
+            input = SAMPLE;
             part1 = 8;
             part2 = 2286;
     }
@@ -125,4 +156,38 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
         part1 = 2505;
         part2 = 70265;
     }
+
+    #[test]
+    fn other_bags() -> Result<()> {
+        let games = Day::get_input(SAMPLE)?;
+        let bag = parse_bag("20 red, 13 green, 15 blue")?;
+        assert_eq!(
+            vec![1, 2, 3, 4, 5],
+            possible_games(&games, &bag).collect::<Vec<_>>()
+        );
+        let bag = parse_bag("4 red, 3 green, 6 blue")?;
+        assert_eq!(vec![1, 2], possible_games(&games, &bag).collect::<Vec<_>>());
+        // Colors the bag doesn't mention count as none at all.
+        let bag = parse_bag("100 red, 100 green")?;
+        assert_eq!(0, possible_games(&games, &bag).count());
+        Ok(())
+    }
+
+    #[test]
+    fn any_colors() -> Result<()> {
+        let games = Day::get_input("Game 7: 2 teal, 1 red; 3 teal, 1 teal\nGame 8: 1 mauve")?;
+        assert_eq!(4, minimum_bag(&games[&7]).count("teal"));
+        assert_eq!(0, minimum_bag(&games[&7]).power());
+        let bag = parse_bag("4 teal, 1 red")?;
+        assert_eq!(vec![7], possible_games(&games, &bag).collect::<Vec<_>>());
+        assert!(Day::explain(&games, "1 mauve")?.ends_with("[8]"));
+        // A color none of the games drew doesn't rule any of them out.
+        let games = Day::get_input(SAMPLE)?;
+        let bag = parse_bag("12 red, 13 green, 14 blue, 3 teal")?;
+        assert_eq!(
+            vec![1, 2, 5],
+            possible_games(&games, &bag).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
 }
@@ -1,103 +1,136 @@
-use std::collections::{HashMap, HashSet};
+use std::{collections::HashMap, ops::Range};
 
 use color_eyre::Result;
 use itertools::Itertools;
 
 use crate::{
-    utils::{eight_neighbors, four_neighbors},
+    utils::{
+        bytes::{digit_runs, lines},
+        eight_neighbors, parse_int,
+    },
     Runner,
 };
 
+/// A number printed on the schematic: its value, its row and the columns its digits cover.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    pub value: usize,
+    pub row: usize,
+    pub cols: Range<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Schematic {
+    shape: (usize, usize),
+    numbers: Vec<Number>,
+    /// Which number covers each cell, as an index into `numbers`.
+    digits: HashMap<(usize, usize), usize>,
+    symbols: HashMap<(usize, usize), char>,
+    by_symbol: HashMap<char, Vec<(usize, usize)>>,
+}
+
+impl Schematic {
+    pub fn parse(input: &str) -> Self {
+        let mut schematic = Self::default();
+        for (row, line) in lines(input.as_bytes()).enumerate() {
+            schematic.shape = (row + 1, schematic.shape.1.max(line.len()));
+            for cols in digit_runs(line) {
+                let idx = schematic.numbers.len();
+                cols.clone().for_each(|col| {
+                    schematic.digits.insert((row, col), idx);
+                });
+                schematic.numbers.push(Number {
+                    value: parse_int(&line[cols.clone()]),
+                    row,
+                    cols,
+                });
+            }
+            line.iter()
+                .enumerate()
+                .filter(|(_, c)| **c != b'.' && !c.is_ascii_digit())
+                .for_each(|(col, &c)| {
+                    schematic.symbols.insert((row, col), c as char);
+                    schematic
+                        .by_symbol
+                        .entry(c as char)
+                        .or_default()
+                        .push((row, col));
+                });
+        }
+        schematic
+    }
+
+    pub fn numbers(&self) -> &[Number] {
+        &self.numbers
+    }
+
+    /// Every place `symbol` appears, which is nowhere if the schematic doesn't use it.
+    pub fn symbols(&self, symbol: char) -> &[(usize, usize)] {
+        self.by_symbol.get(&symbol).map_or(&[], Vec::as_slice)
+    }
+
+    /// The numbers touching `pos`, diagonals included, as indices into [`Schematic::numbers`].
+    pub fn numbers_adjacent_to(&self, pos: (usize, usize)) -> Vec<usize> {
+        eight_neighbors(pos, self.shape)
+            .filter_map(|idx| self.digits.get(&idx).copied())
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// The symbols touching any digit of `self.numbers()[number]`, and where they are.
+    pub fn symbols_adjacent_to(&self, number: usize) -> Vec<(char, (usize, usize))> {
+        let Number { row, cols, .. } = &self.numbers[number];
+        cols.clone()
+            .flat_map(|col| eight_neighbors((*row, col), self.shape))
+            .filter_map(|idx| self.symbols.get(&idx).map(|&symbol| (symbol, idx)))
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// Whether any digit of `self.numbers()[number]` touches a symbol, stopping at the first.
+    pub fn has_adjacent_symbol(&self, number: usize) -> bool {
+        let Number { row, cols, .. } = &self.numbers[number];
+        cols.clone()
+            .flat_map(|col| eight_neighbors((*row, col), self.shape))
+            .any(|idx| self.symbols.contains_key(&idx))
+    }
+}
+
 pub struct Day;
 
 impl Runner for Day {
-    type Input<'input> = (
-        (usize, usize),
-        Vec<usize>,
-        HashMap<(usize, usize), usize>,
-        HashMap<char, Vec<(usize, usize)>>,
-    );
+    type Input<'input> = Schematic;
 
     fn day() -> usize {
         3
     }
 
     fn get_input(input: &str) -> Result<Self::Input<'_>> {
-        let width = input.lines().next().unwrap().chars().count();
-        let height = input.lines().count();
-
-        let lines: Vec<Vec<char>> = input
-            .lines()
-            .map(|line| line.chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-
-        let mut current = None;
-        let mut current_idx = vec![];
-
-        let mut map = HashMap::new();
-        let mut symbols = HashMap::<char, Vec<(usize, usize)>>::new();
-        let mut ids = vec![];
-
-        for row in 0..height {
-            for col in 0..width {
-                let c = lines[row][col];
-                match c {
-                    '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => {
-                        if let Some(ref mut curr) = current {
-                            *curr = *curr * 10 + c as usize - '0' as usize;
-                        } else {
-                            current = Some(c as usize - '0' as usize);
-                        }
-                        current_idx.push((row, col));
-                    }
-                    c => {
-                        if let Some(curr) = current {
-                            let idx = ids.len();
-                            ids.push(curr);
-                            current_idx.drain(..).for_each(|(row, col)| {
-                                map.insert((row, col), idx);
-                            });
-                            current = None;
-                        }
-                        if c != '.' {
-                            symbols.entry(c).or_default().push((row, col));
-                        }
-                    }
-                }
-            }
-        }
-        Ok(((height, width), ids, map, symbols))
+        Ok(Schematic::parse(input))
     }
 
-    fn part1((shape, ref ids, ref map, ref symbols): &Self::Input<'_>) -> Result<usize> {
-        let mut set = HashSet::<usize>::default();
-        symbols.iter().for_each(|(sym, idx)| {
-            // println!("{}: {:?}", sym, idx);
-            for (row, col) in idx {
-                eight_neighbors((*row, *col), *shape).for_each(|idx| {
-                    if map.contains_key(&idx) {
-                        set.insert(*map.get(&idx).unwrap());
-                    }
-                })
-            }
-        });
-        Ok(set.into_iter().map(|idx| ids[idx]).sum())
+    fn part1(input: &Self::Input<'_>) -> Result<usize> {
+        Ok((0..input.numbers().len())
+            .filter(|&idx| input.has_adjacent_symbol(idx))
+            .map(|idx| input.numbers()[idx].value)
+            .sum())
     }
 
-    fn part2((shape, ref ids, ref map, ref symbols): &Self::Input<'_>) -> Result<usize> {
-        let maybe_gears = symbols.get(&'*').unwrap();
-        let gears = maybe_gears
+    fn part2(input: &Self::Input<'_>) -> Result<usize> {
+        Ok(input
+            .symbols('*')
             .iter()
-            .filter_map(|&idx| {
-                let neighbors = eight_neighbors(idx, *shape)
+            .map(|&pos| input.numbers_adjacent_to(pos))
+            .filter(|numbers| numbers.len() == 2)
+            .map(|numbers| {
+                numbers
                     .into_iter()
-                    .filter_map(|id| map.get(&id).and_then(|v| Some(v)))
-                    .collect::<HashSet<_>>();
-                (neighbors.len() == 2)
-                    .then_some(neighbors.into_iter().map(|id| ids[*id]).product::<usize>())
+                    .map(|idx| input.numbers()[idx].value)
+                    .product::<usize>()
             })
-            .collect_vec();
-        Ok(gears.into_iter().sum())
+            .sum())
     }
 }
 
@@ -127,4 +160,25 @@ mod tests {
         part1 = 540212;
         part2 = 87605697;
     }
+
+    #[test]
+    fn adjacency() {
+        let schematic = Schematic::parse("467..114..\n...*......\n..35..633.\n......#...");
+        assert_eq!(vec![0, 2], schematic.numbers_adjacent_to((1, 3)));
+        assert_eq!(vec![('*', (1, 3))], schematic.symbols_adjacent_to(0));
+        assert!(schematic.symbols_adjacent_to(1).is_empty());
+        assert!(schematic.has_adjacent_symbol(0));
+        assert!(!schematic.has_adjacent_symbol(1));
+        assert_eq!(vec![('#', (3, 6))], schematic.symbols_adjacent_to(3));
+        assert_eq!(2..4, schematic.numbers()[2].cols);
+    }
+
+    #[test]
+    fn no_gears() -> Result<()> {
+        let input = Day::get_input("467..114..\n...#......\n..35..633.")?;
+        assert!(input.symbols('*').is_empty());
+        assert_eq!(467 + 35, Day::part1(&input)?);
+        assert_eq!(0, Day::part2(&input)?);
+        Ok(())
+    }
 }
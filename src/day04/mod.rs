@@ -1,28 +1,44 @@
-use std::collections::HashSet;
-
 use color_eyre::Result;
-use itertools::Itertools;
 use winnow::{
-    ascii::{digit1, space1},
-    combinator::{separated, terminated},
+    ascii::{digit1, line_ending, space0, space1},
+    combinator::separated,
+    error::StrContext,
     PResult, Parser,
 };
 
-use crate::Runner;
+use crate::{
+    parsers::{number_list, parse_input},
+    utils::small_set::SmallSet,
+    Runner,
+};
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Card {
-    winners: Vec<usize>,
-    deck: Vec<usize>,
+    pub winners: SmallSet,
+    pub deck: SmallSet,
+    /// How many of the deck's numbers are winners.
+    pub matches: usize,
+}
+
+fn numbers(input: &mut &str) -> PResult<SmallSet> {
+    number_list
+        .verify(|numbers: &Vec<usize>| numbers.iter().all(|&n| n < SmallSet::CAPACITY))
+        .map(SmallSet::from_iter)
+        .context(StrContext::Label("numbers below 128"))
+        .parse_next(input)
 }
 
 fn card(input: &mut &str) -> PResult<Card> {
     let _ = ("Card", space1, digit1, ":", space1).parse_next(input)?;
-    let winners = separated(1.., digit1.try_map(str::parse::<usize>), space1).parse_next(input)?;
+    let winners = numbers.parse_next(input)?;
     let _ = (space1, "|", space1).parse_next(input)?;
-    let deck = separated(1.., digit1.try_map(str::parse::<usize>), space1).parse_next(input)?;
+    let deck = numbers.parse_next(input)?;
 
-    Ok(Card { winners, deck })
+    Ok(Card {
+        winners,
+        deck,
+        matches: (winners & deck).len(),
+    })
 }
 
 pub struct Day;
@@ -35,44 +51,24 @@ impl Runner for Day {
     }
 
     fn get_input(input: &str) -> Result<Self::Input<'_>> {
-        Ok(input
-            .lines()
-            .map(|line| card(&mut line.trim()).unwrap())
-            .collect())
+        Ok(parse_input(
+            separated(1.., card, (space0, line_ending, space0)),
+            input.trim(),
+        )?)
     }
 
     fn part1(input: &Self::Input<'_>) -> Result<usize> {
         Ok(input
-            .into_iter()
-            .map(|card| {
-                card.winners
-                    .iter()
-                    .copied()
-                    .collect::<HashSet<_>>()
-                    .intersection(&card.deck.iter().copied().collect())
-                    .copied()
-                    .collect_vec()
-            })
-            .map(|v| v.len())
-            .filter(|v| *v > 0)
-            .map(|l| 2usize.pow(l as u32 - 1))
+            .iter()
+            .filter(|card| card.matches > 0)
+            .map(|card| 1 << (card.matches - 1))
             .sum())
     }
 
     fn part2(input: &Self::Input<'_>) -> Result<usize> {
         let mut copies = vec![1; input.len()];
-        for (idx, card) in input.into_iter().enumerate() {
-            let wins = card
-                .winners
-                .iter()
-                .copied()
-                .collect::<HashSet<_>>()
-                .intersection(&card.deck.iter().copied().collect())
-                .copied()
-                .collect_vec();
-            if wins.len() > 0 {
-                (1..=wins.len()).for_each(|i| copies[idx + i] += copies[idx]);
-            }
+        for (idx, card) in input.iter().enumerate() {
+            (1..=card.matches).for_each(|i| copies[idx + i] += copies[idx]);
         }
 
         Ok(copies.iter().sum::<usize>())
@@ -101,4 +97,18 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
         part1 = 21485;
         part2 = 201684;
     }
+
+    #[test]
+    fn matches_are_counted_once() -> Result<()> {
+        let cards = Day::get_input("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53")?;
+        assert_eq!(4, cards[0].matches);
+        assert_eq!(
+            vec![17, 48, 83, 86],
+            (cards[0].winners & cards[0].deck)
+                .iter()
+                .collect::<Vec<_>>()
+        );
+        assert!(Day::get_input("Card 1: 1 200 | 3 4").is_err());
+        Ok(())
+    }
 }
@@ -1,122 +1,230 @@
-use std::{
-    collections::{BTreeMap, HashMap},
-    ops::Range,
-};
+use std::ops::Range;
 
 use color_eyre::Result;
 use winnow::{
-    ascii::{digit1, line_ending, multispace0, space1},
-    combinator::separated,
+    ascii::{alpha1, line_ending, multispace0, space1},
+    combinator::{preceded, separated, separated_pair, terminated},
+    error::StrContext,
     PResult, Parser,
 };
 
-use crate::Runner;
+use crate::{
+    parsers::{labelled, number, number_list, parse_input, sections},
+    utils::interval::IntervalSet,
+    Runner, Variant,
+};
 
+/// Two rules in one map claiming some of the same source values.
+#[derive(thiserror::Error, Debug)]
+#[error("source ranges {first:?} and {second:?} overlap")]
+struct OverlappingRules {
+    first: Range<usize>,
+    second: Range<usize>,
+}
+
+/// One almanac map, or several composed into one, sending each source value to its destination.
 #[derive(Debug, Clone, Default)]
-struct RangeChanger {
+pub struct RangeChanger {
+    /// `(source, destination)` rules, sorted by source and never overlapping.
     map: Vec<(Range<usize>, Range<usize>)>,
 }
 
 impl RangeChanger {
-    fn get(&self, src: usize) -> usize {
-        self.map
-            .iter()
-            .find(|(range, _)| range.contains(&src))
+    fn new(mut map: Vec<(Range<usize>, Range<usize>)>) -> Result<Self, OverlappingRules> {
+        map.retain(|(range, _)| !range.is_empty());
+        map.sort_by_key(|(range, _)| range.start);
+        if let Some(pair) = map.windows(2).find(|pair| pair[0].0.end > pair[1].0.start) {
+            return Err(OverlappingRules {
+                first: pair[0].0.clone(),
+                second: pair[1].0.clone(),
+            });
+        }
+        Ok(Self { map })
+    }
+
+    pub fn get(&self, src: usize) -> usize {
+        let idx = self.map.partition_point(|(range, _)| range.start <= src);
+        idx.checked_sub(1)
+            .map(|idx| &self.map[idx])
+            .filter(|(range, _)| range.contains(&src))
             .map(|(range, dst)| dst.start + (src - range.start))
             .unwrap_or(src)
     }
+
+    /// Map every value in `src` at once, splitting its ranges wherever the rules start or stop.
+    pub fn get_ranges(&self, src: &IntervalSet<usize>) -> IntervalSet<usize> {
+        src.map_piecewise(
+            self.map
+                .iter()
+                .map(|(range, dst)| (range.clone(), dst.start)),
+        )
+    }
+
+    /// The rules, plus the identity mapping for every gap between them, covering all of
+    /// `0..usize::MAX` in order as `(source, destination start)` pairs.
+    fn pieces(&self) -> Vec<(Range<usize>, usize)> {
+        let mut pieces = Vec::with_capacity(self.map.len() * 2 + 1);
+        let mut start = 0;
+        for (range, dst) in self
+            .map
+            .iter()
+            .map(|(range, dst)| (range.clone(), dst.start))
+        {
+            if start < range.start {
+                pieces.push((start..range.start, start));
+            }
+            start = range.end;
+            pieces.push((range, dst));
+        }
+        if start < usize::MAX {
+            pieces.push((start..usize::MAX, start));
+        }
+        pieces
+    }
+
+    /// A single map doing `self` and then `next`, so `compose(next).get(x)` is
+    /// `next.get(self.get(x))`.
+    fn compose(&self, next: &Self) -> Self {
+        let after = next.pieces();
+        let mut map = vec![];
+        for (src, dst) in self.pieces() {
+            let image = dst..dst + (src.end - src.start);
+            for (next_src, next_dst) in &after {
+                let overlap = image.start.max(next_src.start)..image.end.min(next_src.end);
+                if overlap.is_empty() {
+                    continue;
+                }
+                let from = src.start + (overlap.start - dst);
+                let to = next_dst + (overlap.start - next_src.start);
+                let len = overlap.end - overlap.start;
+                if from != to {
+                    map.push((from..from + len, to..to + len));
+                }
+            }
+        }
+        Self { map }
+    }
+
+    /// Every value that this map sends into `dst`.
+    fn preimage(&self, dst: &IntervalSet<usize>) -> IntervalSet<usize> {
+        self.pieces()
+            .into_iter()
+            .flat_map(|(src, start)| {
+                let image = IntervalSet::from(start..start + (src.end - src.start));
+                image
+                    .intersection(dst)
+                    .ranges()
+                    .iter()
+                    .map(|r| src.start + (r.start - start)..src.start + (r.end - start))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// One `source-to-destination map:` section of the almanac.
+#[derive(Debug, Clone)]
+struct Stage<'a> {
+    source: &'a str,
+    destination: &'a str,
+    changer: RangeChanger,
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct Maps {
+pub struct Maps<'a> {
     seeds: Vec<usize>,
-    seed_to_soil: RangeChanger,
-    soil_to_fertilizer: RangeChanger,
-    fertilizer_to_water: RangeChanger,
-    water_to_light: RangeChanger,
-    light_to_temperature: RangeChanger,
-    temperature_to_humidity: RangeChanger,
-    humidity_to_location: RangeChanger,
+    stages: Vec<Stage<'a>>,
 }
 
-impl Maps {
+impl<'a> Maps<'a> {
     fn seed_to_location(&self, seed: usize) -> usize {
-        let soil = self.seed_to_soil.get(seed);
-        let fertilizer = self.soil_to_fertilizer.get(soil);
-        let water = self.fertilizer_to_water.get(fertilizer);
-        let light = self.water_to_light.get(water);
-        let temperature = self.light_to_temperature.get(light);
-        let humidity = self.temperature_to_humidity.get(temperature);
-        self.humidity_to_location.get(humidity)
-    }
-
-    fn parse(input: &mut &str) -> PResult<Self> {
-        let mut maps = Self::default();
-        maps.seeds = (
-            "seeds:",
-            space1,
-            separated(1.., digit1.try_map(str::parse::<usize>), space1),
-        )
-            .parse_next(input)?
-            .2;
-        let _ = (multispace0, "seed-to-soil map:", multispace0).parse_next(input)?;
-        let lists = parse_map_list(input)?;
-        mut_hash_map(lists, &mut maps.seed_to_soil);
-        let _ = (multispace0, "soil-to-fertilizer map:", multispace0).parse_next(input)?;
-        let lists = parse_map_list(input)?;
-        mut_hash_map(lists, &mut maps.soil_to_fertilizer);
-        let _ = (multispace0, "fertilizer-to-water map:", multispace0).parse_next(input)?;
-        let lists = parse_map_list(input)?;
-        mut_hash_map(lists, &mut maps.fertilizer_to_water);
-        let _ = (multispace0, "water-to-light map:", multispace0).parse_next(input)?;
-        let lists = parse_map_list(input)?;
-        mut_hash_map(lists, &mut maps.water_to_light);
-        let _ = (multispace0, "light-to-temperature map:", multispace0).parse_next(input)?;
-        let lists = parse_map_list(input)?;
-        mut_hash_map(lists, &mut maps.light_to_temperature);
-        let _ = (multispace0, "temperature-to-humidity map:", multispace0).parse_next(input)?;
-        let lists = parse_map_list(input)?;
-        mut_hash_map(lists, &mut maps.temperature_to_humidity);
-        let _ = (multispace0, "humidity-to-location map:", multispace0).parse_next(input)?;
-        let lists = parse_map_list(input)?;
-        mut_hash_map(lists, &mut maps.humidity_to_location);
-        Ok(maps)
+        self.stages
+            .iter()
+            .fold(seed, |value, stage| stage.changer.get(value))
+    }
+
+    fn seeds_to_locations(&self, seeds: &IntervalSet<usize>) -> IntervalSet<usize> {
+        self.stages.iter().fold(seeds.clone(), |values, stage| {
+            stage.changer.get_ranges(&values)
+        })
+    }
+
+    /// Every seed that ends up at one of `locations`, for searching backwards from the answer.
+    pub fn locations_to_seeds(&self, locations: &IntervalSet<usize>) -> IntervalSet<usize> {
+        self.stages
+            .iter()
+            .rev()
+            .fold(locations.clone(), |values, stage| {
+                stage.changer.preimage(&values)
+            })
+    }
+
+    /// All the stages squashed into one precomputed seed-to-location map.
+    pub fn composed(&self) -> RangeChanger {
+        self.stages
+            .iter()
+            .fold(RangeChanger::default(), |map, stage| {
+                map.compose(&stage.changer)
+            })
+    }
+
+    /// Part 2 reads the seeds as `start length` pairs.
+    fn seed_ranges(&self) -> IntervalSet<usize> {
+        self.seeds
+            .chunks_exact(2)
+            .map(|slice| slice[0]..slice[0] + slice[1])
+            .collect()
     }
-}
 
-fn mut_hash_map(lists: Vec<Vec<usize>>, maps: &mut RangeChanger) {
-    lists.into_iter().for_each(|list| {
-        let [dst, src, cnt] = list.as_slice() else {
-            panic!("malformed map");
-        };
-        maps.map.push((*src..*src + *cnt, *dst..*dst + *cnt));
-    });
+    fn parse(input: &mut &'a str) -> PResult<Self> {
+        let seeds = labelled("seeds:", number_list).parse_next(input)?;
+        let stages = preceded(multispace0, sections(stage))
+            .verify(|stages: &Vec<Stage>| {
+                stages.first().is_some_and(|s| s.source == "seed")
+                    && stages
+                        .windows(2)
+                        .all(|pair| pair[0].destination == pair[1].source)
+            })
+            .context(StrContext::Label(
+                "almanac: each map must start where the previous one ended",
+            ))
+            .parse_next(input)?;
+        Ok(Self { seeds, stages })
+    }
 }
 
-fn parse_map_list(input: &mut &str) -> PResult<Vec<Vec<usize>>> {
-    Ok(separated(
-        1..,
-        separated::<_, usize, Vec<usize>, _, _, _, _>(
-            1..,
-            digit1.try_map(str::parse::<usize>),
-            space1,
-        ),
+fn stage<'a>(input: &mut &'a str) -> PResult<Stage<'a>> {
+    let (source, destination) =
+        terminated(separated_pair(alpha1, "-to-", alpha1), " map:").parse_next(input)?;
+    let changer = preceded(
         line_ending,
+        separated(1.., map_line, line_ending).try_map(RangeChanger::new),
     )
-    .parse_next(input)?)
+    .parse_next(input)?;
+    Ok(Stage {
+        source,
+        destination,
+        changer,
+    })
+}
+
+fn map_line(input: &mut &str) -> PResult<(Range<usize>, Range<usize>)> {
+    let (dst, _, src, _, cnt): (usize, _, usize, _, usize) =
+        (number, space1, number, space1, number).parse_next(input)?;
+    Ok((src..src + cnt, dst..dst + cnt))
 }
 
 pub struct Day;
 
 impl Runner for Day {
-    type Input<'input> = Maps;
+    type Input<'input> = Maps<'input>;
 
     fn day() -> usize {
         5
     }
 
-    fn get_input(mut input: &str) -> Result<Self::Input<'_>> {
-        Ok(Maps::parse(&mut input).unwrap())
+    fn get_input(input: &str) -> Result<Self::Input<'_>> {
+        Ok(parse_input(Maps::parse, input)?)
     }
 
     fn part1(input: &Self::Input<'_>) -> Result<usize> {
@@ -128,30 +236,54 @@ impl Runner for Day {
             .unwrap_or(0))
     }
 
-    // TODO: implement splitting of ranges, and binary-search(?)
     fn part2(input: &Self::Input<'_>) -> Result<usize> {
         Ok(input
-            .seeds
-            .chunks_exact(2)
-            .map(|slice| {
-                (slice[0]..)
-                    .take(slice[1])
-                    .map(|seed| input.seed_to_location(seed))
-            })
-            .flatten()
+            .seeds_to_locations(&input.seed_ranges())
             .min()
             .unwrap_or(0))
     }
+
+    fn variants() -> Vec<Variant<Self>> {
+        vec![Variant {
+            name: "composed",
+            part1: |input| {
+                let map = input.composed();
+                Ok(input
+                    .seeds
+                    .iter()
+                    .map(|&seed| map.get(seed))
+                    .min()
+                    .unwrap_or(0))
+            },
+            part2: |input| {
+                let map = input.composed();
+                Ok(map.get_ranges(&input.seed_ranges()).min().unwrap_or(0))
+            },
+        }]
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+    use proptest::prelude::*;
+
     use super::*;
-    use crate::helpers::{prod_case, sample_case};
+    use crate::helpers::{oracle_case, prod_case, sample_case, variants_case};
 
     sample_case! {
         sample1 =>
-            input = "seeds: 79 14 55 13
+            input = SAMPLE;
+            part1 = 35;
+            part2 = 46;
+    }
+
+    prod_case! {
+        part1 = 323142486;
+        part2 = 201684;
+    }
+
+    const SAMPLE: &str = "seeds: 79 14 55 13
 
 seed-to-soil map:
 50 98 2
@@ -184,12 +316,98 @@ temperature-to-humidity map:
 humidity-to-location map:
 60 56 37
 56 93 4";
-            part1 = 35;
-            part2 = 46;
+
+    #[test]
+    fn composed_map_matches_stages() -> Result<()> {
+        let input = Day::get_input(SAMPLE)?;
+        let composed = input.composed();
+        for seed in 0..200 {
+            assert_eq!(input.seed_to_location(seed), composed.get(seed));
+        }
+        Ok(())
     }
 
-    prod_case! {
-        part1 = 323142486;
-        part2 = 201684;
+    #[test]
+    fn reverse_search() -> Result<()> {
+        let input = Day::get_input(SAMPLE)?;
+        let seeds = input.locations_to_seeds(&IntervalSet::from(46..47));
+        assert!(seeds.contains(&82));
+        for seed in seeds.ranges().iter().flat_map(Clone::clone) {
+            assert_eq!(46, input.seed_to_location(seed));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn broken_chain_is_rejected() {
+        let broken = SAMPLE.replace("water-to-light", "water-to-lava");
+        assert!(Day::get_input(&broken).is_err());
+    }
+
+    #[test]
+    fn overlapping_rules_are_rejected() {
+        let overlapping = SAMPLE.replace("50 98 2\n52 50 48", "50 98 2\n52 50 49");
+        let err = Day::get_input(&overlapping).unwrap_err();
+        assert!(err.to_string().contains("overlap"), "{err}");
+    }
+
+    /// One map's lines: non-overlapping source ranges cut from sorted points, each sent
+    /// somewhere random.
+    fn map_lines() -> impl Strategy<Value = String> {
+        prop::collection::btree_set(0usize..200, 0..10).prop_flat_map(|points| {
+            let sources = points.into_iter().tuples().collect::<Vec<(usize, usize)>>();
+            let destinations = prop::collection::vec(0usize..200, sources.len());
+            (Just(sources), destinations).prop_map(|(sources, destinations)| {
+                sources
+                    .into_iter()
+                    .zip(destinations)
+                    .map(|((start, end), dst)| format!("{dst} {start} {}", end - start))
+                    .join("\n")
+            })
+        })
+    }
+
+    /// A whole almanac with small seed ranges, so part 2 can be checked seed by seed.
+    fn almanacs() -> impl Strategy<Value = String> {
+        let names = ["seed", "soil", "fertilizer", "water", "light", "location"];
+        let seeds = prop::collection::vec((0usize..200, 0usize..20), 1..4);
+        let maps = prop::collection::vec(
+            map_lines().prop_filter("maps need a line", |m| !m.is_empty()),
+            names.len() - 1,
+        );
+        (seeds, maps).prop_map(move |(seeds, maps)| {
+            let seeds = seeds
+                .iter()
+                .map(|(start, len)| format!("{start} {len}"))
+                .join(" ");
+            let maps = names
+                .iter()
+                .tuple_windows()
+                .zip(maps)
+                .map(|((from, to), lines)| format!("{from}-to-{to} map:\n{lines}"))
+                .join("\n\n");
+            format!("seeds: {seeds}\n\n{maps}\n")
+        })
+    }
+
+    fn every_seed(input: &Maps) -> usize {
+        input
+            .seeds
+            .chunks_exact(2)
+            .flat_map(|pair| pair[0]..pair[0] + pair[1])
+            .map(|seed| input.seed_to_location(seed))
+            .min()
+            .unwrap_or(0)
+    }
+
+    oracle_case! {
+        ranges_match_every_seed =>
+            input = almanacs();
+            fast = Day::part2;
+            oracle = every_seed;
+    }
+
+    variants_case! {
+        composed_map_agrees => input = almanacs();
     }
 }
@@ -1,65 +1,136 @@
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use winnow::{
-    ascii::{digit1, multispace0, space1},
-    combinator::separated,
+    ascii::{multispace0, space1},
+    combinator::preceded,
     PResult, Parser,
 };
 
-fn parse_races(input: &mut &str) -> PResult<Vec<(usize, usize)>> {
-    let _ = (multispace0, "Time:", space1).parse_next(input)?;
-    let times: Vec<_> =
-        separated(1.., digit1.try_map(str::parse::<usize>), space1).parse_next(input)?;
-    let _ = (multispace0, "Distance:", space1).parse_next(input)?;
-    let distances: Vec<_> =
-        separated(1.., digit1.try_map(str::parse::<usize>), space1).parse_next(input)?;
+/// The most races a sheet can hold; parsing them never touches the heap.
+const MAX_RACES: usize = 8;
+
+pub type Races = heapless::Vec<(usize, usize), MAX_RACES>;
+
+fn parse_races(input: &mut &str) -> PResult<Races> {
+    let times: heapless::Vec<usize, MAX_RACES> = preceded(
+        multispace0,
+        labelled("Time:", separated_heapless(1, number, space1)),
+    )
+    .parse_next(input)?;
+    let distances: heapless::Vec<usize, MAX_RACES> = preceded(
+        multispace0,
+        labelled("Distance:", separated_heapless(1, number, space1)),
+    )
+    .parse_next(input)?;
 
     Ok(times.into_iter().zip(distances).collect())
 }
 
-use crate::Runner;
+pub mod ring;
+
+use crate::{
+    parsers::{labelled, number, parse_input, separated_heapless},
+    utils::{quadratic_below_zero, RangeIncExt},
+    Runner, Variant,
+};
+
+/// Counts the holds that beat a race's record, given its time and distance.
+pub type Solver = fn(usize, usize) -> usize;
+
+/// The solver [`Day`] uses. The `day06_ring` feature swaps the closed form for the search in
+/// [`ring`].
+#[cfg(not(feature = "day06_ring"))]
+pub const SOLVER: Solver = winning_holds;
+#[cfg(feature = "day06_ring")]
+pub const SOLVER: Solver = ring::winning_holds;
 
 pub struct Day;
 
 impl Runner for Day {
-    type Input<'input> = Vec<(usize, usize)>;
+    type Input<'input> = Races;
 
     fn day() -> usize {
         6
     }
 
-    fn get_input(mut input: &str) -> Result<Self::Input<'_>> {
-        Ok(parse_races(&mut input).expect("failed to parse input"))
+    fn get_input(input: &str) -> Result<Self::Input<'_>> {
+        Ok(parse_input(parse_races, input)?)
     }
 
     fn part1(input: &Self::Input<'_>) -> Result<usize> {
-        Ok(input
-            .into_iter()
-            .copied()
-            .map(|(t, dist)| (0..t).map(|i| (t - i) * i).filter(|&d| d > dist).count())
-            .product::<usize>())
+        Ok(part1_with(input, SOLVER))
     }
 
     fn part2(input: &Self::Input<'_>) -> Result<usize> {
-        let (times, distances): (Vec<_>, Vec<_>) = input.into_iter().copied().unzip();
-        let time = times
-            .into_iter()
-            .map(|t| format!("{t}"))
-            .collect::<String>()
-            .parse::<usize>()?;
-        let distance = distances
-            .into_iter()
-            .map(|d| format!("{d}"))
-            .collect::<String>()
-            .parse::<usize>()?;
-        let first = (0..time / 2).find(|i| (time - i) * i > distance).unwrap();
-        Ok(time - first * 2 + 1)
+        part2_with(input, SOLVER)
+    }
+
+    fn variants() -> Vec<Variant<Self>> {
+        vec![
+            Variant {
+                name: "closed_form",
+                part1: |races| Ok(part1_with(races, winning_holds)),
+                part2: |races| part2_with(races, winning_holds),
+            },
+            Variant {
+                name: "ring",
+                part1: |races| Ok(part1_with(races, ring::winning_holds)),
+                part2: |races| part2_with(races, ring::winning_holds),
+            },
+            Variant {
+                name: "brute",
+                part1: |races| Ok(part1_with(races, brute_force)),
+                part2: |races| part2_with(races, brute_force),
+            },
+        ]
     }
 }
 
+pub fn part1_with(races: &Races, solver: Solver) -> usize {
+    races
+        .iter()
+        .map(|&(time, distance)| solver(time, distance))
+        .product()
+}
+
+pub fn part2_with(races: &Races, solver: Solver) -> Result<usize> {
+    let (time, distance) = races
+        .iter()
+        .try_fold((0, 0), |(time, distance), &(t, d)| {
+            Some((concat(time, t)?, concat(distance, d)?))
+        })
+        .ok_or_else(|| eyre!("the race is too long to fit in a usize once the spaces are gone"))?;
+    Ok(solver(time, distance))
+}
+
+/// How many ways there are to hold the button for a whole number of milliseconds and beat
+/// `distance`: the holds `h` with `(time - h) * h > distance`.
+///
+/// Every product involved is at most `time * time`, which always fits in an `i128`.
+pub fn winning_holds(time: usize, distance: usize) -> usize {
+    quadratic_below_zero(1, -(time as i128), distance as i128).map_or(0, |holds| holds.size())
+}
+
+/// Try every hold.
+pub fn brute_force(time: usize, distance: usize) -> usize {
+    (0..=time)
+        .filter(|hold| (time - hold) * hold > distance)
+        .count()
+}
+
+/// The digits of `b` written after the digits of `a`, as if the spaces were never there, or
+/// `None` if that doesn't fit in a `usize`.
+fn concat(a: usize, b: usize) -> Option<usize> {
+    let shift = 10usize.checked_pow(b.checked_ilog10().unwrap_or(0) + 1)?;
+    a.checked_mul(shift)?.checked_add(b)
+}
+
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+    use proptest::prelude::*;
+
     use super::*;
-    use crate::helpers::{prod_case, sample_case};
+    use crate::helpers::{prod_case, sample_case, variants_case};
 
     sample_case! {
         sample1 =>
@@ -75,4 +146,63 @@ mod tests {
         part1 = 138915;
         part2 = 27340847;
     }
+
+    #[test]
+    fn solvers_match_counting() {
+        let solvers: [(&str, Solver); 2] = [
+            ("closed_form", winning_holds),
+            ("ring", ring::winning_holds),
+        ];
+        for (name, solver) in solvers {
+            for time in 0..60 {
+                for distance in 0..time * time / 4 + 2 {
+                    let expected = brute_force(time, distance);
+                    assert_eq!(
+                        expected,
+                        solver(time, distance),
+                        "{name}: {time} {distance}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn huge_races() -> Result<()> {
+        let time = usize::MAX - 1;
+        assert_eq!(usize::MAX - 2, winning_holds(time, 0));
+        assert_eq!(ring::winning_holds(time, 0), winning_holds(time, 0));
+        assert_eq!(ring::winning_holds(time, time), winning_holds(time, time));
+
+        let input = Day::get_input("Time: 18446744073 709551615\nDistance: 1 2\n")?;
+        assert!(Day::part2(&input).is_ok());
+        let input = Day::get_input("Time: 18446744073 709551616\nDistance: 1 2\n")?;
+        assert!(Day::part2(&input).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_variant() {
+        assert!(Day::variant("ring").is_ok());
+        let err = Day::variant("quantum").err().unwrap();
+        assert!(err.to_string().contains("closed_form"), "{err}");
+    }
+
+    /// A race sheet with up to three races, each record somewhere it can still be beaten or
+    /// just out of reach.
+    fn races() -> impl Strategy<Value = String> {
+        let race = (1usize..100).prop_flat_map(|time| (Just(time), 0..=time * time / 4 + 1));
+        prop::collection::vec(race, 1..=3).prop_map(|races| {
+            let (times, distances): (Vec<_>, Vec<_>) = races.into_iter().unzip();
+            format!(
+                "Time: {}\nDistance: {}\n",
+                times.iter().join(" "),
+                distances.iter().join(" ")
+            )
+        })
+    }
+
+    variants_case! {
+        solvers_agree => input = races();
+    }
 }
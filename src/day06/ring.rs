@@ -0,0 +1,25 @@
+//! Counting the winning holds by searching instead of solving.
+//!
+//! The distance `(time - h) * h` is symmetric about `time / 2` and peaks there, so the winning
+//! holds form one band around the middle of the race. A binary search over the first half finds
+//! where the band starts, and mirroring it gives where it ends.
+
+/// How many holds beat `distance`, found with a binary search rather than a square root.
+pub fn winning_holds(time: usize, distance: usize) -> usize {
+    let beats = |hold: usize| (time - hold) as u128 * hold as u128 > distance as u128;
+    let middle = time / 2;
+    if !beats(middle) {
+        return 0;
+    }
+
+    let (mut low, mut high) = (0, middle);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if beats(mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    time - 2 * low + 1
+}
@@ -1,9 +1,17 @@
-use std::cmp::{Ordering, Reverse};
+use std::{fmt::Display, str::FromStr};
 
 use color_eyre::Result;
-use counter::Counter;
+use winnow::{
+    ascii::{line_ending, space1},
+    combinator::{cut_err, peek, preceded, repeat, separated, separated_pair},
+    token::{any, one_of},
+    PResult, Parser,
+};
 
-use crate::Runner;
+use crate::{
+    parsers::{number, parse_input, BadInput},
+    Runner,
+};
 
 #[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Copy, Debug, Hash)]
 pub enum Card {
@@ -20,12 +28,55 @@ pub enum Card {
     Four = 4,
     Three = 3,
     Two = 2,
-    Joker = 1,
 }
 
 impl Card {
-    fn from_char(c: char) -> Self {
-        match c {
+    /// Every card, weakest first.
+    pub const ALL: [Card; 13] = [
+        Card::Two,
+        Card::Three,
+        Card::Four,
+        Card::Five,
+        Card::Six,
+        Card::Seven,
+        Card::Eight,
+        Card::Nine,
+        Card::Ten,
+        Card::Jack,
+        Card::Queen,
+        Card::King,
+        Card::Ace,
+    ];
+
+    pub fn label(self) -> char {
+        match self {
+            Card::Two => '2',
+            Card::Three => '3',
+            Card::Four => '4',
+            Card::Five => '5',
+            Card::Six => '6',
+            Card::Seven => '7',
+            Card::Eight => '8',
+            Card::Nine => '9',
+            Card::Ten => 'T',
+            Card::Jack => 'J',
+            Card::Queen => 'Q',
+            Card::King => 'K',
+            Card::Ace => 'A',
+        }
+    }
+}
+
+/// A character that isn't one of the thirteen card labels.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("{0:?} is not a card")]
+pub struct InvalidCard(pub char);
+
+impl TryFrom<char> for Card {
+    type Error = InvalidCard;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Ok(match c {
             '2' => Card::Two,
             '3' => Card::Three,
             '4' => Card::Four,
@@ -39,8 +90,8 @@ impl Card {
             'Q' => Card::Queen,
             'K' => Card::King,
             'A' => Card::Ace,
-            _ => panic!(),
-        }
+            _ => return Err(InvalidCard(c)),
+        })
     }
 }
 
@@ -58,122 +109,306 @@ pub enum Type {
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Hand([Card; 5]);
 
-impl Hand {
-    fn get_type(self) -> Type {
-        let mut counters = self.0.into_iter().collect::<Counter<_>>();
+impl Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0
+            .iter()
+            .try_for_each(|card| write!(f, "{}", card.label()))
+    }
+}
 
-        let jokers = counters[&Card::Joker];
-        counters[&Card::Joker] = 0;
+/// How to order two hands of the same type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Compare the cards one by one in the order they were dealt, as the puzzle does.
+    Dealt,
+    /// Compare the strongest cards first, then the next strongest, and so on.
+    Sorted,
+}
 
-        let ordered = counters.most_common_ordered();
-        if ordered[0].1 + jokers == 5 {
-            return Type::FiveOfAKind;
+/// Why a set of [`Rules`] couldn't be built.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RulesError {
+    #[error(transparent)]
+    InvalidCard(#[from] InvalidCard),
+    #[error("the card order must list all 13 cards exactly once")]
+    IncompleteOrder,
+}
+
+/// How hands are scored: how strong each card is, which card is wild, and how ties are broken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// Each card's strength, from 0 for the weakest, indexed by `Card as usize`.
+    ranks: [u8; Card::Ace as usize + 1],
+    wild: Option<Card>,
+    tie_break: TieBreak,
+}
+
+impl Rules {
+    /// Part 1: no wild cards, jacks between tens and queens.
+    pub const STANDARD: Self = Self::new(Card::ALL, None, TieBreak::Dealt);
+
+    /// Part 2: jacks are jokers, wild but the weakest card on their own.
+    pub const JOKERS: Self = Self::new(
+        [
+            Card::Jack,
+            Card::Two,
+            Card::Three,
+            Card::Four,
+            Card::Five,
+            Card::Six,
+            Card::Seven,
+            Card::Eight,
+            Card::Nine,
+            Card::Ten,
+            Card::Queen,
+            Card::King,
+            Card::Ace,
+        ],
+        Some(Card::Jack),
+        TieBreak::Dealt,
+    );
+
+    /// `order` runs from the weakest card to the strongest and must hold each card once.
+    const fn new(order: [Card; 13], wild: Option<Card>, tie_break: TieBreak) -> Self {
+        let mut ranks = [0; Card::Ace as usize + 1];
+        let mut idx = 0;
+        while idx < order.len() {
+            ranks[order[idx] as usize] = idx as u8;
+            idx += 1;
         }
-        if ordered[0].1 + jokers == 4 {
-            return Type::FourOfAKind;
+        Self {
+            ranks,
+            wild,
+            tie_break,
         }
-        if ordered[0].1 + jokers == 3 {
-            if ordered[1].1 == 2 {
-                return Type::FullHouse;
-            } else {
-                return Type::ThreeOfAKind;
+    }
+
+    /// Rules from card labels, e.g. `"J23456789TQKA"` for the joker order, weakest first.
+    pub fn from_labels(
+        order: &str,
+        wild: Option<char>,
+        tie_break: TieBreak,
+    ) -> Result<Self, RulesError> {
+        let order = order
+            .chars()
+            .map(Card::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut distinct = order.clone();
+        distinct.sort();
+        distinct.dedup();
+        let order: [Card; 13] = order
+            .try_into()
+            .ok()
+            .filter(|_| distinct.len() == Card::ALL.len())
+            .ok_or(RulesError::IncompleteOrder)?;
+        let wild = wild.map(Card::try_from).transpose()?;
+        Ok(Self::new(order, wild, tie_break))
+    }
+
+    pub fn rank(&self, card: Card) -> u8 {
+        self.ranks[card as usize]
+    }
+
+    /// The hand's type, with any wild cards standing in for whichever card helps the most.
+    pub fn get_type(&self, hand: Hand) -> Type {
+        let mut counts = [0u8; Card::Ace as usize + 1];
+        hand.0.iter().for_each(|&card| counts[card as usize] += 1);
+        let wild = self
+            .wild
+            .map_or(0, |card| std::mem::take(&mut counts[card as usize]));
+
+        let (mut first, mut second) = (0, 0);
+        for count in counts {
+            if count > first {
+                (first, second) = (count, first);
+            } else if count > second {
+                second = count;
             }
         }
-        if ordered[0].1 + jokers == 2 {
-            if ordered[1].1 == 2 {
-                return Type::TwoPair;
-            } else {
-                return Type::OnePair;
-            }
+        match (first + wild, second) {
+            (5, _) => Type::FiveOfAKind,
+            (4, _) => Type::FourOfAKind,
+            (3, 2) => Type::FullHouse,
+            (3, _) => Type::ThreeOfAKind,
+            (2, 2) => Type::TwoPair,
+            (2, _) => Type::OnePair,
+            _ => Type::HighCard,
+        }
+    }
+
+    /// The card the wild cards copy to make the hand's type: the most common other card, the
+    /// strongest of those on a tie. `None` if the hand has no wild cards.
+    pub fn substitute(&self, hand: Hand) -> Option<Card> {
+        let wild = self.wild.filter(|wild| hand.0.contains(wild))?;
+        let count = |card: Card| hand.0.iter().filter(|&&c| c == card).count();
+        Card::ALL
+            .into_iter()
+            .filter(|&card| card != wild)
+            .max_by_key(|&card| (count(card), self.rank(card)))
+    }
+
+    /// Everything `--explain` says about `hand` when it's played among `plays`.
+    pub fn analyze(&self, hand: Hand, plays: &[Play]) -> Analysis {
+        let key = self.key(hand);
+        Analysis {
+            hand,
+            kind: self.get_type(hand),
+            substitute: self.substitute(hand).zip(self.wild).map(|(card, wild)| {
+                let wilds = hand.0.iter().filter(|&&c| c == wild).count();
+                (wild, wilds, card)
+            }),
+            rank: plays
+                .iter()
+                .filter(|play| self.key(play.hand) < key)
+                .count()
+                + 1,
+            out_of: plays.len(),
         }
-        return Type::HighCard;
     }
 
-    fn to_joker(&mut self) {
-        self.0.iter_mut().for_each(|c| match *c {
-            Card::Jack => *c = Card::Joker,
-            _ => {}
+    /// The type followed by each card's rank, four bits apiece, so comparing keys compares
+    /// hands.
+    pub fn key(&self, hand: Hand) -> u32 {
+        let mut ranks = hand.0.map(|card| self.rank(card));
+        if self.tie_break == TieBreak::Sorted {
+            ranks.sort_unstable_by(|a, b| b.cmp(a));
+        }
+        ranks.iter().fold(self.get_type(hand) as u32, |key, &rank| {
+            key << 4 | rank as u32
         })
     }
 }
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match self.get_type().cmp(&other.get_type()) {
-            Ordering::Equal => {
-                self.0
-                    .iter()
-                    .zip(other.0.iter())
-                    .fold(Ordering::Equal, |acc, (s, o)| match acc {
-                        Ordering::Equal => s.cmp(&o),
-                        other => other,
-                    })
-            }
-            other => other,
+/// How one hand fares under one set of rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Analysis {
+    pub hand: Hand,
+    pub kind: Type,
+    /// The wild card, how many of them the hand holds, and which card they stand in for.
+    pub substitute: Option<(Card, usize, Card)>,
+    /// Where the hand ranks among the plays, 1 being the weakest.
+    pub rank: usize,
+    pub out_of: usize,
+}
+
+impl Display for Analysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.kind)?;
+        if let Some((wild, count, card)) = self.substitute {
+            write!(f, " with {count} {} as {}", wild.label(), card.label())?;
         }
+        write!(f, ", rank {} of {}", self.rank, self.out_of)
     }
 }
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+/// A hand and its bid, with its sort keys worked out up front for both parts.
+#[derive(Debug, Clone, Copy)]
+pub struct Play {
+    pub hand: Hand,
+    pub bid: usize,
+    key: u32,
+    joker_key: u32,
+}
+
+impl Play {
+    fn new(hand: Hand, bid: usize) -> Self {
+        Self {
+            hand,
+            bid,
+            key: Rules::STANDARD.key(hand),
+            joker_key: Rules::JOKERS.key(hand),
+        }
     }
 }
 
+/// Rank the plays by `key`, weakest first, and add up each bid times its rank.
+fn winnings(plays: &[Play], key: impl Fn(&Play) -> u32) -> usize {
+    let mut ranked = plays
+        .iter()
+        .map(|play| (key(play), play.bid))
+        .collect::<Vec<_>>();
+    ranked.sort_unstable();
+    ranked
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (_, bid))| (idx + 1) * bid)
+        .sum()
+}
+
+/// The total winnings with every hand scored under `rules`.
+pub fn winnings_under(plays: &[Play], rules: &Rules) -> usize {
+    winnings(plays, |play| rules.key(play.hand))
+}
+
+/// Anything alphanumeric is meant to be a card, so a bad label is an error rather than the end
+/// of the hand.
+fn card(input: &mut &str) -> PResult<Card> {
+    preceded(
+        peek(one_of(|c: char| c.is_ascii_alphanumeric())),
+        cut_err(any.try_map(Card::try_from)),
+    )
+    .parse_next(input)
+}
+
+fn hand(input: &mut &str) -> PResult<Hand> {
+    repeat(5, card)
+        .map(|cards: Vec<Card>| Hand(cards.try_into().unwrap()))
+        .parse_next(input)
+}
+
+impl FromStr for Hand {
+    type Err = BadInput;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_input(hand, s)
+    }
+}
+
+fn play(input: &mut &str) -> PResult<Play> {
+    separated_pair(hand, space1, number)
+        .map(|(hand, bid)| Play::new(hand, bid))
+        .parse_next(input)
+}
+
 pub struct Day;
 
 impl Runner for Day {
-    type Input<'input> = Vec<(Hand, usize)>;
+    type Input<'input> = Vec<Play>;
 
     fn day() -> usize {
         7
     }
 
     fn get_input(input: &str) -> Result<Self::Input<'_>> {
-        Ok(input
-            .lines()
-            .map(|line| line.split_once(" ").unwrap())
-            .map(|(a, b)| {
-                (
-                    Hand(
-                        a.chars()
-                            .map(Card::from_char)
-                            .collect::<Vec<_>>()
-                            .try_into()
-                            .unwrap(),
-                    ),
-                    b.parse().unwrap(),
-                )
-            })
-            .collect())
+        Ok(parse_input(separated(1.., play, line_ending), input)?)
     }
 
     fn part1(input: &Self::Input<'_>) -> Result<usize> {
-        let mut input = input.to_owned();
-        input.sort_by_key(|t| t.0);
-        Ok(input
-            .into_iter()
-            .enumerate()
-            .map(|(idx, (_, bid))| (idx + 1) * bid)
-            .sum::<usize>())
+        Ok(winnings(input, |play| play.key))
     }
 
     fn part2(input: &Self::Input<'_>) -> Result<usize> {
-        let mut input = input.to_owned();
-        input.iter_mut().for_each(|(h, _)| h.to_joker());
-        input.sort_by_key(|t| t.0);
-        Ok(input
-            .into_iter()
-            .enumerate()
-            .map(|(idx, (_, bid))| (idx + 1) * bid)
-            .sum::<usize>())
+        Ok(winnings(input, |play| play.joker_key))
+    }
+
+    fn explain(input: &Self::Input<'_>, query: &str) -> Result<String> {
+        let hand = query.trim().parse::<Hand>()?;
+        let mut report = hand.to_string();
+        for (name, rules) in [("standard", Rules::STANDARD), ("jokers", Rules::JOKERS)] {
+            report += &format!("\n{name:>8}: {}", rules.analyze(hand, input));
+        }
+        Ok(report)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+    use proptest::prelude::*;
+
     use super::*;
-    use crate::helpers::{prod_case, sample_case};
+    use crate::helpers::{oracle_case, prod_case, sample_case};
 
     sample_case! {
         sample1 =>
@@ -192,4 +427,176 @@ mod tests {
         part1 = 250453939;
         part2 = 248652697;
     }
+
+    fn hand(cards: &str) -> Hand {
+        cards.parse().unwrap()
+    }
+
+    #[test]
+    fn types() {
+        let standard = Rules::STANDARD;
+        assert_eq!(Type::FullHouse, standard.get_type(hand("23332")));
+        assert_eq!(Type::TwoPair, standard.get_type(hand("23432")));
+        assert_eq!(Type::HighCard, standard.get_type(hand("23456")));
+        assert_eq!(Type::OnePair, standard.get_type(hand("QJJ23")));
+
+        let jokers = Rules::JOKERS;
+        assert_eq!(Type::FourOfAKind, jokers.get_type(hand("QJJQ2")));
+        assert_eq!(Type::FullHouse, jokers.get_type(hand("QQJ22")));
+        assert_eq!(Type::FiveOfAKind, jokers.get_type(hand("JJJJJ")));
+        assert_eq!(Type::ThreeOfAKind, jokers.get_type(hand("JJ234")));
+    }
+
+    #[test]
+    fn keys_order_like_hands() {
+        let (standard, jokers) = (Rules::STANDARD, Rules::JOKERS);
+        // Same type, so the first differing card decides.
+        assert!(standard.key(hand("33332")) > standard.key(hand("2AAAA")));
+        assert!(standard.key(hand("KK677")) > standard.key(hand("KTJJT")));
+        // With jokers KTJJT becomes four of a kind and the joker ranks lowest.
+        assert!(jokers.key(hand("KTJJT")) > jokers.key(hand("QQQJA")));
+        assert!(jokers.key(hand("JKKK2")) < jokers.key(hand("QQQQ2")));
+    }
+
+    #[test]
+    fn custom_rules() -> Result<()> {
+        assert_eq!(
+            Rules::JOKERS,
+            Rules::from_labels("J23456789TQKA", Some('J'), TieBreak::Dealt)?
+        );
+
+        // Aces low, twos wild, and the strongest card settles a tie wherever it was dealt.
+        let rules = Rules::from_labels("A23456789TJQK", Some('2'), TieBreak::Sorted)?;
+        assert_eq!(Type::FourOfAKind, rules.get_type(hand("K2K2A")));
+        assert!(rules.key(hand("3K456")) > rules.key(hand("Q3456")));
+        assert!(rules.key(hand("AK345")) < rules.key(hand("2K345")));
+
+        let plays = Day::get_input("32T3K 765\nKTJJT 220\nQQQJA 483")?;
+        assert_eq!(Day::part2(&plays)?, winnings_under(&plays, &Rules::JOKERS));
+
+        assert_eq!(
+            Err(RulesError::InvalidCard(InvalidCard('1'))),
+            Rules::from_labels("123456789TQKA", None, TieBreak::Dealt)
+        );
+        assert_eq!(
+            Err(RulesError::IncompleteOrder),
+            Rules::from_labels("22345678TJQKA", None, TieBreak::Dealt)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_cards_are_reported() {
+        let err = Day::get_input("32T3K 765\nT55X5 684").unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("line 2, column 4"), "{message}");
+        assert!(message.contains("'X' is not a card"), "{message}");
+        assert!("KK67".parse::<Hand>().is_err());
+    }
+
+    #[test]
+    fn substitutes_make_the_type() {
+        // Every hand over a few labels, jokers included, is enough to hit every type.
+        let labels = ['2', 'J', 'Q', 'K'];
+        for idx in 0..labels.len().pow(5) {
+            let cards = (0..5)
+                .map(|n| labels[idx / labels.len().pow(n) % labels.len()])
+                .collect::<String>();
+            let original = hand(&cards);
+            let played = match Rules::JOKERS.substitute(original) {
+                Some(card) => hand(&cards.replace('J', &card.label().to_string())),
+                None => original,
+            };
+            assert_eq!(
+                Rules::JOKERS.get_type(original),
+                Rules::STANDARD.get_type(played),
+                "{original} played as {played}"
+            );
+        }
+    }
+
+    #[test]
+    fn explain() -> Result<()> {
+        let plays = Day::get_input("32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483")?;
+        assert_eq!(
+            "KTJJT\n\
+            standard: TwoPair, rank 2 of 5\n  \
+            jokers: FourOfAKind with 2 J as T, rank 5 of 5",
+            Day::explain(&plays, "KTJJT")?
+        );
+        assert!(Day::explain(&plays, "KTJJ").is_err());
+        Ok(())
+    }
+
+    /// Lists of plays over a handful of labels, so that every type and plenty of ties turn up.
+    fn plays() -> impl Strategy<Value = String> {
+        let hand = prop::collection::vec(prop::sample::select(vec!['2', '9', 'J', 'Q', 'A']), 5);
+        prop::collection::vec((hand, 1usize..1000), 1..50).prop_map(|plays| {
+            plays
+                .into_iter()
+                .map(|(cards, bid)| format!("{} {bid}", cards.into_iter().collect::<String>()))
+                .join("\n")
+        })
+    }
+
+    /// The type by counting pairs the long way, trying every card in place of the jokers.
+    fn brute_type(cards: [Card; 5], jokers: bool) -> Type {
+        let substitutes: &[Card] = if jokers { &Card::ALL } else { &[Card::Jack] };
+        substitutes
+            .iter()
+            .map(|&substitute| {
+                let counts = cards
+                    .map(|c| if c == Card::Jack { substitute } else { c })
+                    .into_iter()
+                    .counts()
+                    .into_values()
+                    .sorted()
+                    .rev()
+                    .collect::<Vec<_>>();
+                match counts.as_slice() {
+                    [5] => Type::FiveOfAKind,
+                    [4, 1] => Type::FourOfAKind,
+                    [3, 2] => Type::FullHouse,
+                    [3, 1, 1] => Type::ThreeOfAKind,
+                    [2, 2, 1] => Type::TwoPair,
+                    [2, 1, 1, 1] => Type::OnePair,
+                    _ => Type::HighCard,
+                }
+            })
+            .max()
+            .unwrap()
+    }
+
+    fn brute_winnings(plays: &[Play], jokers: bool) -> usize {
+        let strength = |card: Card| match card {
+            Card::Jack if jokers => 0,
+            card => card as usize,
+        };
+        plays
+            .iter()
+            .sorted_by_key(|play| {
+                (
+                    brute_type(play.hand.0, jokers),
+                    play.hand.0.map(strength),
+                    play.bid,
+                )
+            })
+            .enumerate()
+            .map(|(idx, play)| (idx + 1) * play.bid)
+            .sum()
+    }
+
+    oracle_case! {
+        part1_matches_brute_force =>
+            input = plays();
+            fast = Day::part1;
+            oracle = |plays: &Vec<Play>| brute_winnings(plays, false);
+    }
+
+    oracle_case! {
+        part2_matches_brute_force =>
+            input = plays();
+            fast = Day::part2;
+            oracle = |plays: &Vec<Play>| brute_winnings(plays, true);
+    }
 }
@@ -1,16 +1,17 @@
 use std::collections::BTreeMap;
 
-use color_eyre::Result;
-use itertools::Itertools;
-use num::Integer;
+use color_eyre::{eyre::eyre, Result};
 use winnow::{
-    ascii::{alpha1, alphanumeric1, multispace0, multispace1},
-    binary::length_data,
-    combinator::{alt, delimited, preceded, repeat},
+    ascii::{alphanumeric1, line_ending, multispace1},
+    combinator::{alt, repeat, separated},
     PResult, Parser,
 };
 
-use crate::Runner;
+use crate::{
+    parsers::{parse_input, record},
+    utils::cycle::{first_common_hit, Hits},
+    Runner,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
@@ -34,24 +35,26 @@ impl<'a> Map<'a> {
     fn parse(input: &mut &'a str) -> PResult<Self> {
         let directions = repeat(1.., Direction::parse).parse_next(input)?;
         let _ = multispace1.parse_next(input)?;
-        let mut map = BTreeMap::new();
-        let repeat: Vec<_> = repeat(1.., Self::parse_path).parse_next(input)?;
-        repeat.into_iter().for_each(|(head, left, right)| {
-            map.insert(head, (left, right));
-        });
-        Ok(Self { directions, map })
-    }
-
-    fn parse_path(input: &mut &'a str) -> PResult<(&'a str, &'a str, &'a str)> {
-        let head = alphanumeric1.parse_next(input)?;
-        let (left, _, right) = delimited(
-            (multispace0, "=", multispace0, "("),
-            (alphanumeric1, (",", multispace0), alphanumeric1),
-            (")", multispace0),
+        let map = separated(
+            1..,
+            record(alphanumeric1, alphanumeric1, alphanumeric1),
+            line_ending,
         )
         .parse_next(input)?;
+        Ok(Self { directions, map })
+    }
 
-        Ok((head, left, right))
+    /// Take one step from `node`, which is `idx` steps into the direction list.
+    ///
+    /// Pairing the node with its position in the directions is what makes the walk a pure
+    /// function of its state, so it can be handed to the cycle detection in `utils`.
+    fn step(&self, &(node, idx): &(&'a str, usize)) -> (&'a str, usize) {
+        let (left, right) = self.map[node];
+        let next = match self.directions[idx] {
+            Direction::Right => right,
+            Direction::Left => left,
+        };
+        (next, (idx + 1) % self.directions.len())
     }
 }
 
@@ -64,8 +67,8 @@ impl Runner for Day {
         8
     }
 
-    fn get_input(mut input: &str) -> Result<Self::Input<'_>> {
-        Ok(Map::parse(&mut input).unwrap())
+    fn get_input(input: &str) -> Result<Self::Input<'_>> {
+        Ok(parse_input(Map::parse, input)?)
     }
 
     fn part1(input: &Self::Input<'_>) -> Result<usize> {
@@ -77,25 +80,21 @@ impl Runner for Day {
     }
 
     fn part2(input: &Self::Input<'_>) -> Result<usize> {
-        let currents = input
+        let walks = input
             .map
             .keys()
-            .filter(|key| key.ends_with("A"))
-            .collect::<Vec<_>>();
-
-        let steps = currents
-            .into_iter()
-            .map(|current| {
-                length_of_cycle(
-                    input.directions.iter().cycle().copied().enumerate(),
-                    current,
-                    input,
+            .filter(|key| key.ends_with('A'))
+            .map(|&start| {
+                Hits::analyze(
+                    (start, 0),
+                    |state| input.step(state),
+                    |(node, _)| node.ends_with('Z'),
                 )
             })
-            .reduce(|a, b| a.lcm(&b))
-            .unwrap();
+            .collect::<Vec<_>>();
 
-        Ok(steps)
+        first_common_hit(&walks)
+            .ok_or_else(|| eyre!("the ghosts never all stand on a Z node at the same time"))
     }
 }
 
@@ -156,4 +155,43 @@ mod tests {
         part1 = 1681;
         part2 = 201684;
     }
+
+    #[test]
+    fn ghosts_reaching_z_before_their_cycle() -> Result<()> {
+        // 11A hits 11Z after 1, 4, 7, ... steps and 22A hits 22Z after 2, 4, 6, ..., so the
+        // first common step is 4 even though the lcm of the first hits is 2.
+        let input = Day::get_input(
+            "\
+            L\n\
+            \n\
+            11A = (11Z, 11Z)\n\
+            11Z = (11B, 11B)\n\
+            11B = (11C, 11C)\n\
+            11C = (11Z, 11Z)\n\
+            22A = (22B, 22B)\n\
+            22B = (22Z, 22Z)\n\
+            22Z = (22B, 22B)\n\
+            ",
+        )?;
+        assert_eq!(4, Day::part2(&input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn ghosts_that_never_meet() -> Result<()> {
+        let input = Day::get_input(
+            "\
+            L\n\
+            \n\
+            11A = (11Z, 11Z)\n\
+            11Z = (11B, 11B)\n\
+            11B = (11Z, 11Z)\n\
+            22A = (22B, 22B)\n\
+            22B = (22Z, 22Z)\n\
+            22Z = (22B, 22B)\n\
+            ",
+        )?;
+        assert!(Day::part2(&input).is_err());
+        Ok(())
+    }
 }
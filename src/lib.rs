@@ -9,13 +9,16 @@ use std::{
     time::{Duration, Instant},
 };
 
-use color_eyre::{eyre::Context, Result};
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
 use tracking_allocator::AllocationRegistry;
 
-use crate::utils::download_input;
+use crate::{parsers::BadInput, utils::download_input};
 
-mod parsers;
-mod utils;
+pub mod parsers;
+pub mod utils;
 
 pub const YEAR: usize = 2023;
 
@@ -23,16 +26,21 @@ macro_rules! run_days {
     ($day:ident = $id:expr, $($days:ident = $ids:expr),* $(,)?) => {
         pub mod $day;
         $(pub mod $days;)*
-        pub fn run(days: Vec<usize>, track: bool) -> Result<Duration> {
+        pub fn run(
+            days: Vec<usize>,
+            track: bool,
+            variant: Option<&str>,
+            check: bool,
+        ) -> Result<Duration> {
             let mut total_time = Duration::ZERO;
             if days.is_empty() {
-                total_time += $day::Day::run(track)?;
-                $(total_time += $days::Day::run(track)?;)*
+                total_time += $day::Day::run(track, variant, check)?;
+                $(total_time += $days::Day::run(track, variant, check)?;)*
             } else {
                 for day in days {
                     total_time += match day {
-                        $id => $day::Day::run(track)?,
-                        $($ids => $days::Day::run(track)?,)*
+                        $id => $day::Day::run(track, variant, check)?,
+                        $($ids => $days::Day::run(track, variant, check)?,)*
                         _ => panic!("Invalid day passed"),
                     }
                 }
@@ -40,6 +48,15 @@ macro_rules! run_days {
 
             Ok(total_time)
         }
+
+        /// Answer `query` about one day's input with [`Runner::explain`].
+        pub fn explain(day: usize, query: &str) -> Result<String> {
+            match day {
+                $id => $day::Day::run_explain(query),
+                $($ids => $days::Day::run_explain(query),)*
+                _ => Err(eyre!("Invalid day passed: {}", day)),
+            }
+        }
     };
 }
 
@@ -54,45 +71,71 @@ run_days!(
     day08 = 8,
 );
 
+/// A named alternative way to solve a day, registered through [`Runner::variants`].
+pub struct Variant<R, Part1 = usize, Part2 = usize>
+where
+    R: Runner<Part1, Part2> + ?Sized,
+    Part1: Debug + PartialEq,
+    Part2: Debug + PartialEq,
+{
+    pub name: &'static str,
+    pub part1: for<'input> fn(&R::Input<'input>) -> Result<Part1>,
+    pub part2: for<'input> fn(&R::Input<'input>) -> Result<Part2>,
+}
+
 pub trait Runner<Part1 = usize, Part2 = usize>
 where
-    Part1: Debug,
-    Part2: Debug,
+    Part1: Debug + PartialEq,
+    Part2: Debug + PartialEq,
 {
     type Input<'input>;
 
-    fn run(track: bool) -> Result<Duration> {
+    /// Solve the day, with `variant` picking one of [`Runner::variants`] instead of `part1` and
+    /// `part2`. With `check`, every variant is run afterwards and has to agree.
+    fn run(track: bool, variant: Option<&str>, check: bool) -> Result<Duration> {
+        // `--variant` applies to every day that runs, so days without it sit this one out.
+        let variant = match variant.map(Self::variant).transpose() {
+            Ok(variant) => variant,
+            Err(err) => {
+                log::warn!("Skipping: {}\n", err);
+                return Ok(Duration::ZERO);
+            }
+        };
         let comment = Self::comment();
         let comment = if comment.is_empty() {
             comment.to_owned()
         } else {
             format!(" : {}", comment)
         };
-        log::info!("Day {}{}\n", Self::day(), comment);
-        let input_path = format!("input/{}/day{:02}.txt", YEAR, Self::day());
-        if !Path::new(&input_path).exists() {
-            dotenv::dotenv().wrap_err("loading .env file")?;
-            let session = std::env::var("AOCSESSION").wrap_err("looking for AOCSESSION env var")?;
-            download_input(Self::day(), YEAR, &session, &input_path)?;
-        }
-        let input = read_to_string(input_path)?;
+        let name = variant
+            .as_ref()
+            .map(|variant| format!(" ({})", variant.name))
+            .unwrap_or_default();
+        log::info!("Day {}{}{}\n", Self::day(), name, comment);
+        let input = Self::read_input()?;
         let now = Instant::now();
         if track {
             AllocationRegistry::enable_tracking();
         }
-        let input = Self::get_input(&input)?;
+        let input = Self::get_input(&input).map_err(render_bad_input)?;
         let elapsed_i = now.elapsed();
         log::info!("Generation took {:?}", elapsed_i);
 
         let now = Instant::now();
-        let output1 = Self::part1(&input);
+        let output1 = match &variant {
+            Some(variant) => (variant.part1)(&input),
+            None => Self::part1(&input),
+        };
         let elapsed1 = now.elapsed();
         let output1 = output1?;
         log::info!("Part 1 - {:?}", output1);
         log::info!("Took {:?}", elapsed1);
 
         let now = Instant::now();
-        let output2 = Self::part2(&input);
+        let output2 = match &variant {
+            Some(variant) => (variant.part2)(&input),
+            None => Self::part2(&input),
+        };
         let elapsed2 = now.elapsed();
         let output2 = output2?;
         if track {
@@ -101,9 +144,78 @@ where
 
         log::info!("Part 2 - {:?}", output2);
         log::info!("Took {:?}\n", elapsed2);
+        if check {
+            Self::cross_check(&input)?;
+            let count = Self::variants().len();
+            if count > 0 {
+                log::info!("All {} variants agree\n", count);
+            }
+        }
         Ok(elapsed_i + elapsed1 + elapsed2)
     }
 
+    /// The day's puzzle input, downloading it first if it isn't on disk yet.
+    fn read_input() -> Result<String> {
+        let input_path = format!("input/{}/day{:02}.txt", YEAR, Self::day());
+        if !Path::new(&input_path).exists() {
+            dotenv::dotenv().wrap_err("loading .env file")?;
+            let session = std::env::var("AOCSESSION").wrap_err("looking for AOCSESSION env var")?;
+            download_input(Self::day(), YEAR, &session, &input_path)?;
+        }
+        Ok(read_to_string(input_path)?)
+    }
+
+    /// Describe something about the parsed input, for days that have a way to show their
+    /// working.
+    fn explain(_: &Self::Input<'_>, _query: &str) -> Result<String> {
+        Err(eyre!("day {} has nothing to explain", Self::day()))
+    }
+
+    fn run_explain(query: &str) -> Result<String> {
+        let input = Self::read_input()?;
+        let input = Self::get_input(&input).map_err(render_bad_input)?;
+        Self::explain(&input, query)
+    }
+
+    /// Other ways of solving this day, to pick between at runtime and bench side by side.
+    fn variants() -> Vec<Variant<Self, Part1, Part2>> {
+        vec![]
+    }
+
+    fn variant(name: &str) -> Result<Variant<Self, Part1, Part2>> {
+        let variants = Self::variants();
+        let names = variants.iter().map(|v| v.name).collect::<Vec<_>>();
+        variants
+            .into_iter()
+            .find(|v| v.name == name)
+            .ok_or_else(|| {
+                eyre!(
+                    "day {} has no variant {:?}, only {:?}",
+                    Self::day(),
+                    name,
+                    names
+                )
+            })
+    }
+
+    /// Make sure every variant gets the same answers as `part1` and `part2`.
+    fn cross_check(input: &Self::Input<'_>) -> Result<()> {
+        let expected = (Self::part1(input)?, Self::part2(input)?);
+        for variant in Self::variants() {
+            let got = ((variant.part1)(input)?, (variant.part2)(input)?);
+            if got != expected {
+                return Err(eyre!(
+                    "day {} variant {:?} answered {:?} instead of {:?}",
+                    Self::day(),
+                    variant.name,
+                    got,
+                    expected
+                ));
+            }
+        }
+        Ok(())
+    }
+
     fn day() -> usize;
     fn comment() -> &'static str {
         ""
@@ -114,6 +226,14 @@ where
     fn part2(_: &Self::Input<'_>) -> Result<Part2>;
 }
 
+/// Render parse failures through miette so the offending line is shown.
+fn render_bad_input(report: color_eyre::Report) -> color_eyre::Report {
+    match report.downcast::<BadInput>() {
+        Ok(bad) => eyre!("{:?}", miette::Report::new(bad)),
+        Err(report) => report,
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod helpers {
     macro_rules! sample_case {
@@ -128,6 +248,9 @@ pub(crate) mod helpers {
                     let input = Day::get_input(input)?;
                     println!("{:#?}", input);
                     assert_eq!($part1, Day::part1(&input)?);
+                    for variant in Day::variants() {
+                        assert_eq!($part1, (variant.part1)(&input)?, "{}", variant.name);
+                    }
                     Ok(())
                 }
 
@@ -138,6 +261,9 @@ pub(crate) mod helpers {
                     let input = Day::get_input(input)?;
                     println!("{:#?}", input);
                     assert_eq!($part2, Day::part2(&input)?);
+                    for variant in Day::variants() {
+                        assert_eq!($part2, (variant.part2)(&input)?, "{}", variant.name);
+                    }
                     Ok(())
                 }
             }
@@ -153,6 +279,9 @@ pub(crate) mod helpers {
                     let input = Day::get_input(input)?;
                     println!("{:#?}", input);
                     assert_eq!($part1, Day::part1(&input)?);
+                    for variant in Day::variants() {
+                        assert_eq!($part1, (variant.part1)(&input)?, "{}", variant.name);
+                    }
                     Ok(())
                 }
 
@@ -163,6 +292,9 @@ pub(crate) mod helpers {
                     let input = Day::get_input(input)?;
                     println!("{:#?}", input);
                     assert_eq!($part2, Day::part2(&input)?);
+                    for variant in Day::variants() {
+                        assert_eq!($part2, (variant.part2)(&input)?, "{}", variant.name);
+                    }
                     Ok(())
                 }
             }
@@ -196,6 +328,38 @@ pub(crate) mod helpers {
         };
     }
 
+    /// Property tests: for every input text the strategy generates, `fast` and `oracle` have to
+    /// agree. Both take the parsed input, so the parser gets exercised as well.
+    macro_rules! oracle_case {
+        ($id:ident => input = $strategy:expr; fast = $fast:expr; oracle = $oracle:expr;) => {
+            proptest::proptest! {
+                #[test]
+                fn $id(text in $strategy) {
+                    let input = Day::get_input(&text).unwrap();
+                    proptest::prop_assert_eq!($oracle(&input), $fast(&input).unwrap());
+                }
+            }
+        };
+    }
+
+    /// Property tests that every one of the day's [`crate::Runner::variants`] agrees with its
+    /// `part1` and `part2` on generated inputs.
+    macro_rules! variants_case {
+        ($id:ident => input = $strategy:expr;) => {
+            proptest::proptest! {
+                #[test]
+                fn $id(text in $strategy) {
+                    let input = Day::get_input(&text).unwrap();
+                    if let Err(err) = Day::cross_check(&input) {
+                        proptest::prop_assert!(false, "{}", err);
+                    }
+                }
+            }
+        };
+    }
+
+    pub(crate) use oracle_case;
     pub(crate) use prod_case;
     pub(crate) use sample_case;
+    pub(crate) use variants_case;
 }
@@ -1,7 +1,7 @@
 use std::sync::atomic::AtomicBool;
 
 use clap::{ArgAction, Parser};
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use fern::colors::{Color, ColoredLevelConfig};
 use mimalloc::MiMalloc;
 use tracking_allocator::{AllocationGroupId, AllocationRegistry, AllocationTracker, Allocator};
@@ -85,6 +85,19 @@ struct Args {
 
     #[arg(short = 'p', long = "panic")]
     panic: bool,
+
+    /// Solve with this named alternative instead of the usual parts, skipping the days that
+    /// don't have it.
+    #[arg(short, long)]
+    variant: Option<String>,
+
+    /// Run every variant as well and fail if any of them disagree.
+    #[arg(short, long)]
+    check: bool,
+
+    /// Instead of solving, ask each day given with `--days` to explain this, e.g. a day 7 hand.
+    #[arg(short, long)]
+    explain: Option<String>,
 }
 
 fn main() -> Result<()> {
@@ -97,7 +110,22 @@ fn main() -> Result<()> {
         PANIC_ON_ALLOCATE.store(true, std::sync::atomic::Ordering::SeqCst);
     }
 
-    let time = aoc2023::run(days, args.track_allocations)?;
+    if let Some(query) = args.explain {
+        if days.is_empty() {
+            return Err(eyre!("--explain needs a day to ask"));
+        }
+        for day in days {
+            log::info!("Day {}\n{}", day, aoc2023::explain(day, &query)?);
+        }
+        return Ok(());
+    }
+
+    let time = aoc2023::run(
+        days,
+        args.track_allocations,
+        args.variant.as_deref(),
+        args.check,
+    )?;
     log::info!("Total Time: {:?}", time);
 
     Ok(())
@@ -1,110 +1,397 @@
-use std::fmt::Debug;
-
-use heapless::Vec;
-// use nom::{
-//     bytes::complete::tag,
-//     character::complete::{digit1, multispace0, one_of},
-//     combinator::{map, opt},
-//     error::{ErrorKind, ParseError},
-//     multi::many1,
-//     sequence::{terminated, tuple},
-//     AsBytes, AsChar, Err, IResult, InputLength, InputTakeAtPosition, Parser,
-// };
-// use nom_locate::LocatedSpan;
-// use nom_supreme::error::BaseErrorKind;
-
-// use crate::utils::parse_int;
-
-// #[allow(dead_code)]
-// pub type Span<'a> = LocatedSpan<&'a str>;
-
-// #[derive(thiserror::Error, Debug, miette::Diagnostic)]
-// #[error("Bad input")]
-// pub struct BadInput<'input> {
-//     #[source_code]
-//     src: &'input str,
-
-//     #[label("{kind}")]
-//     bad_bit: miette::SourceSpan,
-
-//     kind: BaseErrorKind<&'input str, Box<dyn std::error::Error + Send + Sync + 'input>>,
-// }
-
-// pub fn single_digit_line<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], std::vec::Vec<usize>, E>
-// where
-//     E: ParseError<&'a [u8]>,
-// {
-//     terminated(
-//         many1(map(one_of("0123456789"), |s| (s as u8 - b'0') as usize)),
-//         multispace0,
-//     )(input)
-// }
-
-// pub fn number<S, U, E>(input: S) -> IResult<S, U, E>
-// where
-//     S: AsBytes + Clone + InputTakeAtPosition,
-//     U: TryFrom<usize>,
-//     E: ParseError<S>,
-//     <S as InputTakeAtPosition>::Item: AsChar,
-//     <U as TryFrom<usize>>::Error: Debug,
-// {
-//     map(digit1, |d: S| U::try_from(parse_int(d.as_bytes())).unwrap())(input)
-// }
-
-// pub fn signed_number<'input, E>(input: &'input [u8]) -> IResult<&'input [u8], isize, E>
-// where
-//     E: ParseError<&'input [u8]>,
-// {
-//     map(
-//         tuple((opt(tag(b"-")), digit1)),
-//         |(s, d): (Option<&[u8]>, &[u8])| {
-//             let parsed = parse_int(d.as_bytes()) as isize;
-//             if s.is_some() {
-//                 -parsed
-//             } else {
-//                 parsed
-//             }
-//         },
-//     )(input)
-// }
-
-// #[allow(dead_code)]
-// pub fn many1_heapless<I, O, E, F, const N: usize>(
-//     mut f: F,
-// ) -> impl FnMut(I) -> IResult<I, Vec<O, N>, E>
-// where
-//     I: Clone + InputLength,
-//     F: Parser<I, O, E>,
-//     E: ParseError<I>,
-// {
-//     move |mut i: I| match f.parse(i.clone()) {
-//         Err(Err::Error(err)) => Err(Err::Error(E::append(i, ErrorKind::Many1, err))),
-//         Err(e) => Err(e),
-//         Ok((i1, o)) => {
-//             let mut acc = heapless::Vec::<_, N>::new();
-//             acc.push(o)
-//                 .map_err(|_| panic!("vector not big enough"))
-//                 .unwrap();
-//             i = i1;
-
-//             loop {
-//                 let len = i.input_len();
-//                 match f.parse(i.clone()) {
-//                     Err(Err::Error(_)) => return Ok((i, acc)),
-//                     Err(e) => return Err(e),
-//                     Ok((i1, o)) => {
-//                         // infinite loop check: the parser must always consume
-//                         if i1.input_len() == len {
-//                             return Err(Err::Error(E::from_error_kind(i, ErrorKind::Many1)));
-//                         }
-
-//                         i = i1;
-//                         acc.push(o)
-//                             .map_err(|_| panic!("vector not big enough"))
-//                             .unwrap();
-//                     }
-//                 }
-//             }
-//         }
-//     }
-// }
+use std::str::FromStr;
+
+use miette::SourceSpan;
+use winnow::{
+    ascii::{digit1, line_ending, multispace0, space0, space1},
+    combinator::{delimited, opt, preceded, repeat, separated, separated_pair, terminated},
+    error::{ContextError, ErrMode, ErrorKind, FromExternalError, ParseError, ParserError},
+    stream::Stream,
+    token::one_of,
+    PResult, Parser,
+};
+
+/// A parse failure, carrying a copy of the input so `miette` can point at the offending line.
+#[derive(thiserror::Error, Debug, miette::Diagnostic)]
+#[error("Bad input on line {line}, column {column}: {kind}")]
+pub struct BadInput {
+    #[source_code]
+    src: String,
+
+    #[label("{kind}")]
+    bad_bit: SourceSpan,
+
+    line: usize,
+    column: usize,
+    kind: String,
+}
+
+impl BadInput {
+    /// Build the diagnostic for a failure `offset` bytes into `src`.
+    ///
+    /// The label covers the rest of the offending line, starting at `offset`.
+    pub fn new(src: &str, offset: usize, kind: impl ToString) -> Self {
+        let offset = offset.min(src.len());
+        let line_start = src[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+        let line_end = src[offset..]
+            .find('\n')
+            .map_or(src.len(), |idx| offset + idx);
+        let kind = kind.to_string();
+        let kind = if kind.is_empty() {
+            "unexpected input".to_owned()
+        } else {
+            kind
+        };
+        Self {
+            src: src.to_owned(),
+            bad_bit: (offset, line_end - offset).into(),
+            line: src[..offset].matches('\n').count() + 1,
+            column: src[line_start..offset].chars().count() + 1,
+            kind,
+        }
+    }
+}
+
+impl From<ParseError<&str, ContextError>> for BadInput {
+    fn from(err: ParseError<&str, ContextError>) -> Self {
+        Self::new(err.input(), err.offset(), describe(err.inner()))
+    }
+}
+
+fn describe(err: &ContextError) -> String {
+    match err.cause() {
+        Some(cause) if err.context().next().is_some() => format!("{err}: {cause}"),
+        Some(cause) => cause.to_string(),
+        None => err.to_string(),
+    }
+}
+
+/// Run `parser` over the whole of `input`, allowing trailing whitespace, and turn any failure
+/// into a [`BadInput`].
+pub fn parse_input<'i, O, P>(parser: P, input: &'i str) -> Result<O, BadInput>
+where
+    P: Parser<&'i str, O, ContextError>,
+{
+    Ok(terminated(parser, multispace0).parse(input)?)
+}
+
+/// A line of single digits, e.g. `30373`.
+pub fn single_digit_line(input: &mut &str) -> PResult<Vec<usize>> {
+    terminated(
+        repeat(
+            1..,
+            one_of('0'..='9').map(|c: char| c as usize - '0' as usize),
+        ),
+        multispace0,
+    )
+    .parse_next(input)
+}
+
+/// An unsigned integer of any type that can be parsed from a string of digits.
+pub fn number<T>(input: &mut &str) -> PResult<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    digit1.try_map(str::parse).parse_next(input)
+}
+
+/// An integer with an optional leading `-` or `+`.
+pub fn signed_number<T>(input: &mut &str) -> PResult<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    (opt(one_of(['-', '+'])), digit1)
+        .recognize()
+        .try_map(str::parse)
+        .parse_next(input)
+}
+
+/// One or more unsigned numbers separated by spaces, e.g. `79 14 55 13`.
+pub fn number_list<T>(input: &mut &str) -> PResult<Vec<T>>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    separated(1.., number, space1).parse_next(input)
+}
+
+/// `label` followed by `parser`, e.g. `Time:      7  15   30`.
+///
+/// The value may start on the same line or on the line after the label, so this also covers
+/// section headers like `seed-to-soil map:`.
+pub fn labelled<'i, O, P>(label: &'static str, parser: P) -> impl Parser<&'i str, O, ContextError>
+where
+    P: Parser<&'i str, O, ContextError>,
+{
+    preceded((label, space0, opt(line_ending), space0), parser)
+}
+
+/// A rectangle of cells, one row per line.
+pub fn grid<'i, O, P>(cell: P) -> impl Parser<&'i str, Vec<Vec<O>>, ContextError>
+where
+    P: Parser<&'i str, O, ContextError>,
+{
+    separated(1.., repeat(1.., cell), line_ending)
+}
+
+/// One or more sections separated by blank lines.
+pub fn sections<'i, O, P>(section: P) -> impl Parser<&'i str, Vec<O>, ContextError>
+where
+    P: Parser<&'i str, O, ContextError>,
+{
+    separated(1.., section, (line_ending, space0, line_ending))
+}
+
+/// A `key = (left, right)` record, as in `AAA = (BBB, CCC)`.
+pub fn record<'i, K, A, B, PK, PA, PB>(
+    key: PK,
+    left: PA,
+    right: PB,
+) -> impl Parser<&'i str, (K, (A, B)), ContextError>
+where
+    PK: Parser<&'i str, K, ContextError>,
+    PA: Parser<&'i str, A, ContextError>,
+    PB: Parser<&'i str, B, ContextError>,
+{
+    separated_pair(
+        key,
+        (space0, '=', space0),
+        delimited(
+            ('(', space0),
+            separated_pair(left, (',', space0), right),
+            (space0, ')'),
+        ),
+    )
+}
+
+/// A fixed-capacity collection filled up before its parser ran out of items.
+#[derive(thiserror::Error, Debug)]
+#[error("more than {capacity} items")]
+pub struct CapacityExceeded {
+    capacity: usize,
+}
+
+fn capacity_exceeded(input: &&str, capacity: usize) -> ErrMode<ContextError> {
+    ErrMode::Cut(ContextError::from_external_error(
+        input,
+        ErrorKind::Many,
+        CapacityExceeded { capacity },
+    ))
+}
+
+/// Like `repeat(min.., parser)`, but collects into a `heapless::Vec` so nothing is allocated.
+///
+/// Running out of capacity is a [`CapacityExceeded`] error pointing at the item that didn't fit.
+pub fn repeat_heapless<'i, O, P, const N: usize>(
+    min: usize,
+    mut parser: P,
+) -> impl Parser<&'i str, heapless::Vec<O, N>, ContextError>
+where
+    P: Parser<&'i str, O, ContextError>,
+{
+    move |input: &mut &'i str| {
+        let mut acc = heapless::Vec::new();
+        loop {
+            let start = input.checkpoint();
+            let len = input.eof_offset();
+            match parser.parse_next(input) {
+                Err(ErrMode::Backtrack(e)) => {
+                    input.reset(start);
+                    if acc.len() < min {
+                        return Err(ErrMode::Backtrack(e.append(input, ErrorKind::Many)));
+                    }
+                    return Ok(acc);
+                }
+                Err(e) => return Err(e),
+                Ok(o) => {
+                    // infinite loop check: the parser must always consume
+                    if input.eof_offset() == len {
+                        return Err(ErrMode::Cut(ContextError::from_error_kind(
+                            input,
+                            ErrorKind::Assert,
+                        )));
+                    }
+                    if acc.push(o).is_err() {
+                        input.reset(start);
+                        return Err(capacity_exceeded(input, N));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Like `separated(min.., parser, sep)`, but collects into a `heapless::Vec` so nothing is
+/// allocated.
+///
+/// Running out of capacity is a [`CapacityExceeded`] error pointing at the item that didn't fit.
+pub fn separated_heapless<'i, O, O2, P, S, const N: usize>(
+    min: usize,
+    mut parser: P,
+    mut sep: S,
+) -> impl Parser<&'i str, heapless::Vec<O, N>, ContextError>
+where
+    P: Parser<&'i str, O, ContextError>,
+    S: Parser<&'i str, O2, ContextError>,
+{
+    move |input: &mut &'i str| {
+        let mut acc = heapless::Vec::new();
+        let start = input.checkpoint();
+        match parser.parse_next(input) {
+            Err(ErrMode::Backtrack(e)) => {
+                input.reset(start);
+                if min > 0 {
+                    return Err(ErrMode::Backtrack(e.append(input, ErrorKind::Many)));
+                }
+                return Ok(acc);
+            }
+            Err(e) => return Err(e),
+            Ok(o) => {
+                if acc.push(o).is_err() {
+                    input.reset(start);
+                    return Err(capacity_exceeded(input, N));
+                }
+            }
+        }
+
+        loop {
+            let start = input.checkpoint();
+            let len = input.eof_offset();
+            match sep.parse_next(input) {
+                Err(ErrMode::Backtrack(_)) => {
+                    input.reset(start);
+                    break;
+                }
+                Err(e) => return Err(e),
+                Ok(_) => {}
+            }
+            let item = input.checkpoint();
+            match parser.parse_next(input) {
+                Err(ErrMode::Backtrack(_)) => {
+                    input.reset(start);
+                    break;
+                }
+                Err(e) => return Err(e),
+                Ok(o) => {
+                    // infinite loop check: the parsers must always consume
+                    if input.eof_offset() == len {
+                        return Err(ErrMode::Cut(ContextError::from_error_kind(
+                            input,
+                            ErrorKind::Assert,
+                        )));
+                    }
+                    if acc.push(o).is_err() {
+                        input.reset(item);
+                        return Err(capacity_exceeded(input, N));
+                    }
+                }
+            }
+        }
+
+        if acc.len() < min {
+            return Err(ErrMode::Backtrack(ContextError::from_error_kind(
+                input,
+                ErrorKind::Many,
+            )));
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_input_points_at_line() {
+        let input = "1 2 3\n4 x 6\n";
+        let err = parse_input(
+            separated::<_, Vec<_>, Vec<_>, _, _, _, _>(
+                1..,
+                separated::<_, _, Vec<_>, _, _, _, _>(1.., digit1, ' '),
+                '\n',
+            ),
+            input,
+        )
+        .unwrap_err();
+        assert_eq!(2, err.line);
+        assert_eq!(3, err.column);
+        assert_eq!(SourceSpan::from((8, 3)), err.bad_bit);
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Ok(42u8), number::<u8>.parse("42"));
+        assert!(number::<u8>.parse("420").is_err());
+        assert_eq!(Ok(-17i64), signed_number::<i64>.parse("-17"));
+        assert_eq!(Ok(17i32), signed_number::<i32>.parse("+17"));
+        assert_eq!(
+            Ok(vec![79u64, 14, 55, 13]),
+            number_list.parse("79 14  55 13")
+        );
+    }
+
+    #[test]
+    fn labelled_headers() {
+        assert_eq!(
+            Ok(vec![7usize, 15, 30]),
+            labelled("Time:", number_list).parse("Time:      7  15   30")
+        );
+        assert_eq!(
+            Ok(vec![50usize, 98, 2]),
+            labelled("seed-to-soil map:", number_list).parse("seed-to-soil map:\n50 98 2")
+        );
+    }
+
+    #[test]
+    fn grids_and_sections() {
+        assert_eq!(
+            Ok(vec![vec!['#', '.'], vec!['.', '#']]),
+            grid(one_of(['#', '.'])).parse("#.\n.#")
+        );
+        assert_eq!(
+            Ok(vec![vec![1usize, 2], vec![3]]),
+            sections(separated(1.., number::<usize>, line_ending)).parse("1\n2\n\n3")
+        );
+        assert_eq!(Ok(vec![3, 0, 3, 7, 3]), single_digit_line.parse("30373\n"));
+    }
+
+    #[test]
+    fn records() {
+        assert_eq!(
+            Ok(("AAA", ("BBB", "CCC"))),
+            record(
+                winnow::ascii::alphanumeric1,
+                winnow::ascii::alphanumeric1,
+                winnow::ascii::alphanumeric1
+            )
+            .parse("AAA = (BBB, CCC)")
+        );
+    }
+
+    #[test]
+    fn heapless_collections() {
+        let parsed: heapless::Vec<u8, 4> =
+            separated_heapless(1, number, ' ').parse("1 2 3").unwrap();
+        assert_eq!(&[1, 2, 3], parsed.as_slice());
+
+        let err = parse_input(
+            separated_heapless::<u8, _, _, _, 2>(1, number, ' '),
+            "1 2 3",
+        )
+        .unwrap_err();
+        assert_eq!(SourceSpan::from((4, 1)), err.bad_bit);
+        assert_eq!("more than 2 items", err.kind);
+
+        let parsed: heapless::Vec<char, 4> =
+            repeat_heapless(1, one_of('0'..='9')).parse("123").unwrap();
+        assert_eq!(&['1', '2', '3'], parsed.as_slice());
+        assert!(repeat_heapless::<char, _, 2>(1, one_of('0'..='9'))
+            .parse("123")
+            .is_err());
+        assert!(repeat_heapless::<char, _, 2>(1, one_of('0'..='9'))
+            .parse("")
+            .is_err());
+    }
+}
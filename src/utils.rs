@@ -1,3 +1,12 @@
+pub mod bytes;
+pub mod cycle;
+pub mod direction;
+pub mod grid;
+pub mod interval;
+pub mod matcher;
+pub mod point;
+pub mod search;
+pub mod small_set;
 pub(crate) mod top;
 
 use std::{
@@ -9,6 +18,8 @@ use std::{
 use color_eyre::{eyre::eyre, Result};
 use ndarray::{Array2, Axis};
 
+use self::point::{Point2, Point3};
+
 use reqwest::{
     blocking::Client,
     header::{HeaderMap, HeaderValue, CONTENT_TYPE, COOKIE},
@@ -37,6 +48,64 @@ pub fn parse_int(b: &[u8]) -> usize {
     b.iter().fold(0, |a, c| a * 10 + (c & 0x0f) as usize)
 }
 
+/// The integers `x` where `a * x * x + b * x + c < 0`.
+///
+/// Starts from the integer square root of the discriminant and then nudges each end by
+/// evaluating the polynomial exactly, so there is no floating point to lose precision on large
+/// inputs. Returns `None` when there are no such integers, when `a <= 0` so the parabola doesn't
+/// open upwards, or when the arithmetic would overflow.
+pub fn quadratic_below_zero(a: i128, b: i128, c: i128) -> Option<RangeInclusive<i128>> {
+    if a <= 0 {
+        return None;
+    }
+    let eval = |x: i128| {
+        a.checked_mul(x)?
+            .checked_add(b)?
+            .checked_mul(x)?
+            .checked_add(c)
+    };
+    // The integer closest to the vertex; if even that isn't below zero, nothing is.
+    let two_a = a.checked_mul(2)?;
+    let vertex = b.checked_neg()?.div_euclid(two_a);
+    let vertex = if eval(vertex.checked_add(1)?)? < eval(vertex)? {
+        vertex + 1
+    } else {
+        vertex
+    };
+    if eval(vertex)? >= 0 {
+        return None;
+    }
+    // `b * b` alone can pass `i128::MAX`, so the discriminant is worked out unsigned.
+    let b_squared = b.unsigned_abs().checked_mul(b.unsigned_abs())?;
+    let four_ac = a
+        .unsigned_abs()
+        .checked_mul(c.unsigned_abs())?
+        .checked_mul(4)?;
+    let discriminant = if c < 0 {
+        b_squared.checked_add(four_ac)?
+    } else {
+        b_squared.checked_sub(four_ac)?
+    };
+    // The square root of a `u128` is below `2^64`, so it always fits.
+    let root = discriminant.isqrt() as i128;
+
+    let mut low = (-b).checked_sub(root)?.div_euclid(two_a).min(vertex);
+    while eval(low)? >= 0 {
+        low += 1;
+    }
+    while eval(low.checked_sub(1)?)? < 0 {
+        low -= 1;
+    }
+    let mut high = ((-b).checked_add(root)?.div_euclid(two_a) + 1).max(vertex);
+    while eval(high)? >= 0 {
+        high -= 1;
+    }
+    while eval(high.checked_add(1)?)? < 0 {
+        high += 1;
+    }
+    Some(low..=high)
+}
+
 pub fn download_input(day: usize, year: usize, session: &str, filename: &str) -> Result<()> {
     let url = format!("https://adventofcode.com/{}/day/{}/input", year, day);
     log::info!("Downloading: {}", url);
@@ -68,16 +137,9 @@ pub fn four_neighbors(
     idx: (usize, usize),
     shape: (usize, usize),
 ) -> impl Iterator<Item = (usize, usize)> {
-    [
-        (idx.0 as isize - 1, idx.1 as isize),
-        (idx.0 as isize, idx.1 as isize - 1),
-        (idx.0 as isize + 1, idx.1 as isize),
-        (idx.0 as isize, idx.1 as isize + 1),
-    ]
-    .into_iter()
-    .filter(|&(x, y)| x >= 0 && y >= 0)
-    .filter(move |&(x, y)| x < shape.0 as isize && y < shape.1 as isize)
-    .map(|(x, y)| (x as usize, y as usize))
+    Point2::from_index(idx)
+        .four_neighbors()
+        .filter_map(move |p| p.to_index(shape))
 }
 
 #[allow(dead_code)]
@@ -85,25 +147,14 @@ pub fn eight_neighbors(
     idx: (usize, usize),
     shape: (usize, usize),
 ) -> impl Iterator<Item = (usize, usize)> {
-    itertools::iproduct!(-1..=1isize, -1..=1isize)
-        .filter(move |&(x, y)| x != 0 || y != 0)
-        .filter(move |&(x, y)| idx.0 as isize + x >= 0 && idx.1 as isize + y >= 0)
-        .filter(move |&(x, y)| {
-            idx.0 as isize + x < (shape.0 as isize) && idx.1 as isize + y < (shape.1 as isize)
-        })
-        .map(move |(x, y)| ((idx.0 as isize + x) as usize, (idx.1 as isize + y) as usize))
+    Point2::from_index(idx)
+        .eight_neighbors()
+        .filter_map(move |p| p.to_index(shape))
 }
+
 #[allow(dead_code)]
 pub fn six_neighbors(idx: [isize; 3]) -> impl Iterator<Item = [isize; 3]> {
-    [
-        [idx[0] - 1, idx[1], idx[2]],
-        [idx[0], idx[1] - 1, idx[2]],
-        [idx[0], idx[1], idx[2] - 1],
-        [idx[0] + 1, idx[1], idx[2]],
-        [idx[0], idx[1] + 1, idx[2]],
-        [idx[0], idx[1], idx[2] + 1],
-    ]
-    .into_iter()
+    Point3::from(idx).six_neighbors().map(Into::into)
 }
 
 #[allow(dead_code)]
@@ -169,7 +220,12 @@ where
     }
 
     fn size(&self) -> usize {
-        (*self.end() - *self.start()).try_into().unwrap()
+        if self.is_empty() {
+            return 0;
+        }
+        // Both ends are included, so `3..=7` holds 5 values.
+        let size: usize = (*self.end() - *self.start()).try_into().unwrap();
+        size + 1
     }
 }
 
@@ -206,3 +262,35 @@ where
         (self.end - self.start).try_into().unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quadratic_roots() {
+        // (x - 2)(x - 6) = x^2 - 8x + 12
+        assert_eq!(Some(3..=5), quadratic_below_zero(1, -8, 12));
+        // (2x - 3)(x - 4) = 2x^2 - 11x + 12, negative for 1.5 < x < 4
+        assert_eq!(Some(2..=3), quadratic_below_zero(2, -11, 12));
+        assert_eq!(None, quadratic_below_zero(1, -4, 4));
+        assert_eq!(None, quadratic_below_zero(1, 0, 1));
+
+        let (t, h) = (2_000_000_000_000i128, 123_456_789_012i128);
+        assert_eq!(
+            Some(h + 1..=t - h - 1),
+            quadratic_below_zero(1, -t, h * (t - h))
+        );
+
+        assert_eq!(None, quadratic_below_zero(0, -8, 12));
+        assert_eq!(None, quadratic_below_zero(-1, 8, -12));
+        assert_eq!(None, quadratic_below_zero(i128::MAX, i128::MIN, 0));
+    }
+
+    #[test]
+    fn inclusive_size() {
+        assert_eq!(5, (3..=7).size());
+        assert_eq!(1, (3..=3).size());
+        assert_eq!(4, (3..7).size());
+    }
+}
@@ -0,0 +1,111 @@
+//! Byte-level scanners for inputs where parsing dominates the runtime.
+//!
+//! Everything here works on `&[u8]` with plain slice searches (`position`, `split`) that the
+//! compiler can vectorize, and never allocates.
+
+use std::ops::Range;
+
+use super::parse_int;
+
+/// Lines of `input`, without their `\n` (or `\r\n`) terminators.
+///
+/// A trailing newline does not produce an empty last line.
+pub fn lines(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let input = input.strip_suffix(b"\n").unwrap_or(input);
+    input
+        .split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(move |_| !input.is_empty())
+}
+
+/// Byte ranges of every run of ASCII digits in `line`.
+pub fn digit_runs(line: &[u8]) -> DigitRuns<'_> {
+    DigitRuns { line, pos: 0 }
+}
+
+/// Iterator returned by [`digit_runs`].
+#[derive(Debug, Clone)]
+pub struct DigitRuns<'a> {
+    line: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for DigitRuns<'a> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.line[self.pos..];
+        let start = self.pos + rest.iter().position(u8::is_ascii_digit)?;
+        let len = self.line[start..]
+            .iter()
+            .position(|b| !b.is_ascii_digit())
+            .unwrap_or(self.line.len() - start);
+        self.pos = start + len;
+        Some(start..self.pos)
+    }
+}
+
+/// Every unsigned integer in `line`, ignoring whatever separates them.
+pub fn numbers(line: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    digit_runs(line).map(|run| parse_int(&line[run]))
+}
+
+/// Every integer in `line`; a `-` directly before a run of digits makes it negative.
+pub fn signed_numbers(line: &[u8]) -> impl Iterator<Item = isize> + '_ {
+    digit_runs(line).map(|run| {
+        let value = parse_int(&line[run.clone()]) as isize;
+        if run.start > 0 && line[run.start - 1] == b'-' {
+            -value
+        } else {
+            value
+        }
+    })
+}
+
+/// The `width` bytes starting at `start`, trimmed of surrounding whitespace.
+///
+/// Fields running past the end of the line are cut short rather than panicking.
+pub fn field(line: &[u8], start: usize, width: usize) -> &[u8] {
+    let start = start.min(line.len());
+    let end = (start + width).min(line.len());
+    super::trim_ascii_whitespace(&line[start..end])
+}
+
+/// The unsigned number in a fixed-width column, e.g. `Card  7:` has its id in `field(line, 5, 3)`.
+pub fn field_number(line: &[u8], start: usize, width: usize) -> usize {
+    parse_int(field(line, start, width))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_lines() {
+        let lines = lines(b"ab\r\ncd\n\nef\n").collect::<Vec<_>>();
+        assert_eq!(vec![&b"ab"[..], b"cd", b"", b"ef"], lines);
+        assert_eq!(0, super::lines(b"").count());
+    }
+
+    #[test]
+    fn scans_numbers() {
+        let line = b"467..114..*-12";
+        assert_eq!(
+            vec![0..3, 5..8, 12..14],
+            digit_runs(line).collect::<Vec<_>>()
+        );
+        assert_eq!(vec![467, 114, 12], numbers(line).collect::<Vec<_>>());
+        assert_eq!(
+            vec![467, 114, -12],
+            signed_numbers(line).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn reads_fields() {
+        let line = b"Card  7: 41 48";
+        assert_eq!(b"Card", field(line, 0, 4));
+        assert_eq!(7, field_number(line, 5, 2));
+        assert_eq!(48, field_number(line, 12, 10));
+    }
+}
@@ -0,0 +1,278 @@
+//! Cycle detection for iterated state machines, and combining several cycles with the Chinese
+//! remainder theorem.
+//!
+//! A walk `x0, f(x0), f(f(x0)), ...` over a finite state space always ends up in a loop: after
+//! `offset` steps it repeats every `period` steps.
+
+use itertools::Itertools;
+
+/// Where a walk starts looping: state `offset + period` is the same as state `offset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub offset: usize,
+    pub period: usize,
+}
+
+/// Floyd's tortoise and hare.
+pub fn floyd<S: Eq + Clone>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut tortoise = step(&start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let next = step(&hare);
+        hare = step(&next);
+    }
+
+    let mut offset = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        offset += 1;
+    }
+
+    let mut period = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        period += 1;
+    }
+
+    Cycle { offset, period }
+}
+
+/// Brent's algorithm; the same answer as [`floyd`] with fewer calls to `step`.
+pub fn brent<S: Eq + Clone>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    tortoise = start.clone();
+    hare = start;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut offset = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        offset += 1;
+    }
+
+    Cycle { offset, period }
+}
+
+/// The steps of a walk at which some condition holds, described finitely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hits {
+    pub cycle: Cycle,
+    /// Hits before the walk enters its loop, all less than `cycle.offset`.
+    pub prefix: Vec<usize>,
+    /// Hits during the first time round the loop, in `cycle.offset..cycle.offset + cycle.period`.
+    /// Each one repeats every `cycle.period` steps.
+    pub looping: Vec<usize>,
+}
+
+impl Hits {
+    /// Find the loop in the walk from `start` and record every step at which `is_hit` holds.
+    pub fn analyze<S: Eq + Clone>(
+        start: S,
+        mut step: impl FnMut(&S) -> S,
+        mut is_hit: impl FnMut(&S) -> bool,
+    ) -> Self {
+        let cycle = brent(start.clone(), &mut step);
+        let mut prefix = vec![];
+        let mut looping = vec![];
+        let mut state = start;
+        for idx in 0..cycle.offset + cycle.period {
+            if is_hit(&state) {
+                if idx < cycle.offset {
+                    prefix.push(idx);
+                } else {
+                    looping.push(idx);
+                }
+            }
+            state = step(&state);
+        }
+        Self {
+            cycle,
+            prefix,
+            looping,
+        }
+    }
+
+    pub fn is_hit(&self, n: usize) -> bool {
+        if n < self.cycle.offset {
+            self.prefix.contains(&n)
+        } else {
+            let n = self.cycle.offset + (n - self.cycle.offset) % self.cycle.period;
+            self.looping.contains(&n)
+        }
+    }
+
+    /// Every hit, in order. Infinite unless the loop never hits.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        let looping = (0..).flat_map(move |lap| {
+            self.looping
+                .iter()
+                .map(move |n| n + lap * self.cycle.period)
+        });
+        self.prefix.iter().copied().chain(
+            // Without this check an empty loop would spin forever looking for the next hit.
+            (!self.looping.is_empty())
+                .then_some(looping)
+                .into_iter()
+                .flatten(),
+        )
+    }
+}
+
+/// The first step at which every walk hits at once, if there is one.
+pub fn first_common_hit(walks: &[Hits]) -> Option<usize> {
+    let settled = walks.iter().map(|w| w.cycle.offset).max()?;
+
+    // Before every walk is looping, a common hit must be in some walk's prefix.
+    let early = walks
+        .iter()
+        .flat_map(|w| w.prefix.iter().copied())
+        .filter(|&n| walks.iter().all(|w| w.is_hit(n)))
+        .min();
+    if early.is_some() {
+        return early;
+    }
+
+    // After that, each walk hits exactly on a set of residues modulo its period.
+    walks
+        .iter()
+        .map(|w| {
+            w.looping
+                .iter()
+                .map(|&n| (n % w.cycle.period, w.cycle.period))
+                .collect_vec()
+        })
+        .multi_cartesian_product()
+        .filter_map(crt)
+        .map(|(residue, modulus)| {
+            if residue >= settled {
+                residue
+            } else {
+                residue + (settled - residue).div_ceil(modulus) * modulus
+            }
+        })
+        .min()
+}
+
+/// Solve `x ≡ residue (mod modulus)` for every pair at once, where the moduli need not be
+/// coprime.
+///
+/// Returns the smallest non-negative solution and the modulus it repeats with, or `None` if the
+/// congruences contradict each other.
+pub fn crt(congruences: impl IntoIterator<Item = (usize, usize)>) -> Option<(usize, usize)> {
+    let (residue, modulus) = congruences
+        .into_iter()
+        .map(|(r, m)| (r as i128, m as i128))
+        .try_fold((0i128, 1i128), |(r1, m1), (r2, m2)| {
+            let (g, p, _) = extended_gcd(m1, m2);
+            if (r2 - r1) % g != 0 {
+                return None;
+            }
+            let lcm = m1 / g * m2;
+            let k = ((r2 - r1) / g * p).rem_euclid(m2 / g);
+            Some(((r1 + m1 * k).rem_euclid(lcm), lcm))
+        })?;
+    Some((residue as usize, modulus as usize))
+}
+
+/// `(g, x, y)` with `a * x + b * y == g == gcd(a, b)`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - a / b * y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3 -> ...
+    fn rho(x: &usize) -> usize {
+        if *x == 6 {
+            3
+        } else {
+            x + 1
+        }
+    }
+
+    #[test]
+    fn finds_cycles() {
+        let expected = Cycle {
+            offset: 3,
+            period: 4,
+        };
+        assert_eq!(expected, floyd(0, rho));
+        assert_eq!(expected, brent(0, rho));
+        assert_eq!(
+            Cycle {
+                offset: 0,
+                period: 4
+            },
+            brent(4, rho)
+        );
+    }
+
+    #[test]
+    fn records_hits() {
+        let hits = Hits::analyze(0, rho, |x| x % 2 == 1);
+        assert_eq!(vec![1], hits.prefix);
+        assert_eq!(vec![3, 5], hits.looping);
+        assert_eq!(
+            vec![1, 3, 5, 7, 9, 11],
+            hits.iter().take(6).collect::<Vec<_>>()
+        );
+        assert!(hits.is_hit(9));
+        assert!(!hits.is_hit(10));
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        assert_eq!(Some((23, 105)), crt([(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some((10, 12)), crt([(4, 6), (2, 4)]));
+        assert_eq!(None, crt([(1, 4), (2, 6)]));
+    }
+
+    #[test]
+    fn common_hits() {
+        let every = |period: usize, first: usize| Hits {
+            cycle: Cycle { offset: 0, period },
+            prefix: vec![],
+            looping: vec![first],
+        };
+        assert_eq!(Some(0), first_common_hit(&[every(2, 0), every(3, 0)]));
+        assert_eq!(Some(6), first_common_hit(&[every(4, 2), every(6, 0)]));
+        assert_eq!(None, first_common_hit(&[every(4, 1), every(6, 0)]));
+
+        let late = Hits {
+            cycle: Cycle {
+                offset: 5,
+                period: 2,
+            },
+            prefix: vec![2],
+            looping: vec![5],
+        };
+        assert_eq!(Some(2), first_common_hit(&[late.clone(), every(2, 0)]));
+        assert_eq!(Some(9), first_common_hit(&[late, every(3, 0)]));
+    }
+}
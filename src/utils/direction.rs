@@ -0,0 +1,107 @@
+use std::ops::Mul;
+
+use super::point::Point2;
+
+/// One of the four compass directions, with north pointing up the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// A change of heading relative to the current [`Direction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Turn {
+    Left,
+    Straight,
+    Right,
+    Back,
+}
+
+impl Direction {
+    /// Every direction, clockwise from north.
+    pub const ALL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    /// The unit step in this direction.
+    pub const fn offset(self) -> Point2 {
+        match self {
+            Direction::North => Point2::new(0, -1),
+            Direction::East => Point2::new(1, 0),
+            Direction::South => Point2::new(0, 1),
+            Direction::West => Point2::new(-1, 0),
+        }
+    }
+
+    pub const fn clockwise(self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    pub const fn counterclockwise(self) -> Self {
+        match self {
+            Direction::North => Direction::West,
+            Direction::East => Direction::North,
+            Direction::South => Direction::East,
+            Direction::West => Direction::South,
+        }
+    }
+
+    pub const fn opposite(self) -> Self {
+        self.clockwise().clockwise()
+    }
+
+    pub const fn turn(self, turn: Turn) -> Self {
+        match turn {
+            Turn::Left => self.counterclockwise(),
+            Turn::Straight => self,
+            Turn::Right => self.clockwise(),
+            Turn::Back => self.opposite(),
+        }
+    }
+
+    pub const fn is_vertical(self) -> bool {
+        matches!(self, Direction::North | Direction::South)
+    }
+}
+
+impl Turn {
+    /// The turn that takes `from` to `to`.
+    pub fn between(from: Direction, to: Direction) -> Self {
+        [Turn::Straight, Turn::Right, Turn::Back, Turn::Left]
+            .into_iter()
+            .find(|&turn| from.turn(turn) == to)
+            .unwrap()
+    }
+}
+
+impl Mul<isize> for Direction {
+    type Output = Point2;
+
+    fn mul(self, rhs: isize) -> Self::Output {
+        self.offset() * rhs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns() {
+        assert_eq!(Direction::West, Direction::North.turn(Turn::Left));
+        assert_eq!(Direction::South, Direction::West.turn(Turn::Left));
+        assert_eq!(Direction::East, Direction::West.opposite());
+        for from in Direction::ALL {
+            for to in Direction::ALL {
+                assert_eq!(to, from.turn(Turn::between(from, to)));
+            }
+            assert_eq!(Point2::ORIGIN, from.offset() + from.opposite().offset());
+        }
+    }
+}
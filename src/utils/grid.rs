@@ -0,0 +1,263 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use color_eyre::{eyre::eyre, Result};
+use ndarray::{Array2, ArrayView1, Axis};
+
+use super::{eight_neighbors, four_neighbors, point::Point2};
+
+/// A rectangle of cells indexed by `(row, col)`, backed by an [`Array2`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Array2<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(cells: Array2<T>) -> Self {
+        Self { cells }
+    }
+
+    /// Build a grid from its rows, which must all be the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if let Some(row) = rows.iter().position(|row| row.len() != width) {
+            return Err(eyre!(
+                "row {} has {} cells, expected {}",
+                row,
+                rows[row].len(),
+                width
+            ));
+        }
+        let cells = Array2::from_shape_vec((height, width), rows.into_iter().flatten().collect())?;
+        Ok(Self { cells })
+    }
+
+    /// Parse one cell per byte, one row per line.
+    pub fn parse(input: &str, mut cell: impl FnMut(u8) -> Result<T>) -> Result<Self> {
+        let rows = super::bytes::lines(input.as_bytes())
+            .map(|line| line.iter().map(|&b| cell(b)).collect::<Result<Vec<_>>>())
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_rows(rows)
+    }
+
+    /// `(rows, cols)`, in the same form the neighbor helpers in `utils` take.
+    pub fn shape(&self) -> (usize, usize) {
+        self.cells.dim()
+    }
+
+    pub fn height(&self) -> usize {
+        self.shape().0
+    }
+
+    pub fn width(&self) -> usize {
+        self.shape().1
+    }
+
+    pub fn get(&self, idx: (usize, usize)) -> Option<&T> {
+        self.cells.get(idx)
+    }
+
+    pub fn get_mut(&mut self, idx: (usize, usize)) -> Option<&mut T> {
+        self.cells.get_mut(idx)
+    }
+
+    /// Look up the cell under `point`, returning `None` off the edge.
+    pub fn get_point(&self, point: Point2) -> Option<&T> {
+        self.get(point.to_index(self.shape())?)
+    }
+
+    /// Look up a cell from signed coordinates, returning `None` off the edge.
+    pub fn get_signed(&self, (row, col): (isize, isize)) -> Option<&T> {
+        let row = usize::try_from(row).ok()?;
+        let col = usize::try_from(col).ok()?;
+        self.get((row, col))
+    }
+
+    /// Look up a cell as if the grid tiled the plane in every direction.
+    pub fn get_wrapping(&self, (row, col): (isize, isize)) -> &T {
+        let (height, width) = self.shape();
+        &self.cells[(
+            row.rem_euclid(height as isize) as usize,
+            col.rem_euclid(width as isize) as usize,
+        )]
+    }
+
+    /// Every cell along with its index, row by row.
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.cells.indexed_iter()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// The orthogonal neighbors of `idx` that are inside the grid.
+    pub fn four_neighbors(
+        &self,
+        idx: (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        four_neighbors(idx, self.shape()).map(|idx| (idx, &self.cells[idx]))
+    }
+
+    /// The orthogonal and diagonal neighbors of `idx` that are inside the grid.
+    pub fn eight_neighbors(
+        &self,
+        idx: (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        eight_neighbors(idx, self.shape()).map(|idx| (idx, &self.cells[idx]))
+    }
+
+    pub fn row(&self, row: usize) -> ArrayView1<'_, T> {
+        self.cells.row(row)
+    }
+
+    pub fn column(&self, col: usize) -> ArrayView1<'_, T> {
+        self.cells.column(col)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = ArrayView1<'_, T>> {
+        self.cells.axis_iter(Axis(0))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = ArrayView1<'_, T>> {
+        self.cells.axis_iter(Axis(1))
+    }
+
+    /// The cells from `idx` down and to the right, until the edge.
+    pub fn diagonal(&self, idx: (usize, usize)) -> impl Iterator<Item = &T> {
+        (0..).map_while(move |step| self.get((idx.0 + step, idx.1 + step)))
+    }
+
+    /// The cells from `idx` down and to the left, until the edge.
+    pub fn anti_diagonal(&self, idx: (usize, usize)) -> impl Iterator<Item = &T> {
+        (0..=idx.1).map_while(move |step| self.get((idx.0 + step, idx.1 - step)))
+    }
+
+    pub fn into_inner(self) -> Array2<T> {
+        self.cells
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(shape: (usize, usize), value: T) -> Self {
+        Self::new(Array2::from_elem(shape, value))
+    }
+
+    pub fn transposed(&self) -> Self {
+        Self::new(self.cells.t().to_owned())
+    }
+
+    /// The grid turned a quarter turn clockwise: the first column becomes the first row, read
+    /// bottom to top.
+    pub fn rotated_clockwise(&self) -> Self {
+        let mut cells = self.cells.t();
+        cells.invert_axis(Axis(1));
+        Self::new(cells.to_owned())
+    }
+
+    /// The grid turned a quarter turn counterclockwise: the last column becomes the first row.
+    pub fn rotated_counterclockwise(&self) -> Self {
+        let mut cells = self.cells.t();
+        cells.invert_axis(Axis(0));
+        Self::new(cells.to_owned())
+    }
+}
+
+impl Grid<char> {
+    /// A grid of the characters in `input`, one row per line.
+    pub fn from_chars(input: &str) -> Result<Self> {
+        Self::from_rows(input.lines().map(|line| line.chars().collect()).collect())
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, idx: (usize, usize)) -> &Self::Output {
+        &self.cells[idx]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, idx: (usize, usize)) -> &mut Self::Output {
+        &mut self.cells[idx]
+    }
+}
+
+impl<T> Index<Point2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point2) -> &Self::Output {
+        self.get_point(point).expect("point outside of grid")
+    }
+}
+
+impl<T> IndexMut<Point2> for Grid<T> {
+    fn index_mut(&mut self, point: Point2) -> &mut Self::Output {
+        let idx = point.to_index(self.shape()).expect("point outside of grid");
+        &mut self.cells[idx]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for c in row {
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_indexes() -> Result<()> {
+        let grid = Grid::from_chars("ab\ncd\nef\n")?;
+        assert_eq!((3, 2), grid.shape());
+        assert_eq!('d', grid[(1, 1)]);
+        assert_eq!(None, grid.get((3, 0)));
+        assert_eq!(None, grid.get_signed((-1, 0)));
+        assert_eq!(&'f', grid.get_wrapping((-1, 3)));
+        assert_eq!('c', grid[Point2::new(0, 1)]);
+        assert_eq!(None, grid.get_point(Point2::new(2, 0)));
+        assert!(Grid::from_chars("ab\nc").is_err());
+
+        let digits = Grid::parse("12\n34", |b| Ok((b - b'0') as usize))?;
+        assert_eq!(10, digits.iter().sum::<usize>());
+        Ok(())
+    }
+
+    #[test]
+    fn neighbors_and_views() -> Result<()> {
+        let grid = Grid::from_chars("abc\ndef\nghi")?;
+        let mut around = grid
+            .eight_neighbors((0, 0))
+            .map(|(_, c)| *c)
+            .collect::<Vec<_>>();
+        around.sort();
+        assert_eq!(vec!['b', 'd', 'e'], around);
+        assert_eq!(2, grid.four_neighbors((0, 0)).count());
+        assert_eq!("beh", grid.column(1).iter().collect::<String>());
+        assert_eq!("aei", grid.diagonal((0, 0)).collect::<String>());
+        assert_eq!("ceg", grid.anti_diagonal((0, 2)).collect::<String>());
+        Ok(())
+    }
+
+    #[test]
+    fn transforms() -> Result<()> {
+        let grid = Grid::from_chars("ab\ncd\nef")?;
+        assert_eq!("ace\nbdf\n", grid.transposed().to_string());
+        assert_eq!("eca\nfdb\n", grid.rotated_clockwise().to_string());
+        assert_eq!("bdf\nace\n", grid.rotated_counterclockwise().to_string());
+        assert_eq!(grid, grid.rotated_clockwise().rotated_counterclockwise());
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    fmt::Debug,
+    ops::{Add, Range, Sub},
+};
+
+use super::RangeIncExt;
+
+/// A set of values stored as disjoint, sorted, non-adjacent half-open ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self { ranges: vec![] }
+    }
+}
+
+impl<T: Ord + Copy> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The disjoint ranges making up the set, in increasing order.
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The smallest value in the set.
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let idx = self.ranges.partition_point(|r| r.end <= *value);
+        self.ranges.get(idx).is_some_and(|r| r.contains(value))
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let idx = self.ranges.partition_point(|r| r.end < range.start);
+        let mut merged = range;
+        while idx < self.ranges.len() && self.ranges[idx].start <= merged.end {
+            let r = self.ranges.remove(idx);
+            merged = merged.start.min(r.start)..merged.end.max(r.end);
+        }
+        self.ranges.insert(idx, merged);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        other.ranges.iter().for_each(|r| union.insert(r.clone()));
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let overlap = a.start.max(b.start)..a.end.min(b.end);
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges }
+    }
+
+    /// Everything in `self` that is not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let mut j = 0;
+        for range in &self.ranges {
+            let mut start = range.start;
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < range.end {
+                let cut = &other.ranges[k];
+                if cut.start > start {
+                    ranges.push(start..cut.start);
+                }
+                start = start.max(cut.end);
+                k += 1;
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        Self { ranges }
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Ord + Copy + Sub<T>,
+    <T as Sub<T>>::Output: TryInto<usize>,
+    <<T as Sub<T>>::Output as TryInto<usize>>::Error: Debug,
+{
+    /// How many values are in the set.
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map(RangeIncExt::size).sum()
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Ord + Copy + Add<Output = T> + Sub<Output = T>,
+{
+    /// Send each value through a piecewise translation.
+    ///
+    /// Each rule `(source, destination)` moves the values in `source` so that `source.start` lands
+    /// on `destination`. Values outside every rule are kept as they are. The rules' sources
+    /// must not overlap each other.
+    pub fn map_piecewise(&self, rules: impl IntoIterator<Item = (Range<T>, T)>) -> Self {
+        let mut unmapped = self.clone();
+        let mut mapped = Self::new();
+        for (source, destination) in rules {
+            let hit = unmapped.intersection(&Self::from(source.clone()));
+            hit.ranges.iter().for_each(|r| {
+                mapped.insert(
+                    destination + (r.start - source.start)..destination + (r.end - source.start),
+                )
+            });
+            unmapped = unmapped.difference(&hit);
+        }
+        mapped.union(&unmapped)
+    }
+}
+
+impl<T: Ord + Copy> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut ranges = iter
+            .into_iter()
+            .filter(|r| !r.is_empty())
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|r| r.start);
+        let mut merged: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        Self { ranges: merged }
+    }
+}
+
+impl<T: Ord + Copy> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        Self::from_iter([range])
+    }
+}
+
+impl<T: Ord + Copy> Extend<Range<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = Range<T>>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|r| self.insert(r));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[Range<i64>]) -> IntervalSet<i64> {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn normalizes() {
+        let s = set(&[5..8, 0..2, 7..10, 2..3, 12..12]);
+        assert_eq!(&[0..3, 5..10], s.ranges());
+        assert_eq!(8, s.len());
+        assert!(s.contains(&9));
+        assert!(!s.contains(&10));
+        assert!(!s.contains(&4));
+
+        let mut s = s;
+        s.insert(3..5);
+        assert_eq!(std::slice::from_ref(&(0..10)), s.ranges());
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = set(&[0..10, 20..30]);
+        let b = set(&[5..25, 28..40]);
+        assert_eq!(std::slice::from_ref(&(0..40)), a.union(&b).ranges());
+        assert_eq!(&[5..10, 20..25, 28..30], a.intersection(&b).ranges());
+        assert_eq!(&[0..5, 25..28], a.difference(&b).ranges());
+        assert_eq!(&[10..20, 30..40], b.difference(&a).ranges());
+    }
+
+    #[test]
+    fn piecewise_mapping() {
+        // The first rule of the day05 sample's seed-to-soil map, and then the second.
+        let rules = [(98..100, 50), (50..98, 52)];
+        let seeds = set(&[79..93, 55..68, 97..100]);
+        assert_eq!(
+            &[50..52, 57..70, 81..95, 99..100],
+            seeds.map_piecewise(rules).ranges()
+        );
+    }
+}
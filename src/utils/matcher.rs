@@ -0,0 +1,170 @@
+//! An Aho–Corasick automaton for finding many byte patterns in one pass.
+//!
+//! All the work happens in [`Matcher::new`]: the trie's failure links are folded into a full
+//! transition table, so scanning is one table lookup per byte and never allocates. The scan
+//! takes any iterator of bytes, so running a matcher built from reversed patterns over
+//! `haystack.iter().rev()` searches from the end.
+
+use std::collections::VecDeque;
+
+/// Marks a missing transition while building, and "no state" in the output links.
+const NONE: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+pub struct Matcher<V> {
+    /// `next[state * 256 + byte]` is the state after reading `byte`.
+    next: Vec<u32>,
+    /// The length and value of the pattern ending at each state, if any.
+    outputs: Vec<Option<(usize, V)>>,
+    /// The closest state along the failure links that ends a pattern, or [`NONE`].
+    output_links: Vec<u32>,
+}
+
+impl<V: Copy> Matcher<V> {
+    /// A matcher for every `(pattern, value)` pair. Empty patterns never match, and for
+    /// repeated patterns the last value wins.
+    pub fn new<P: AsRef<[u8]>>(patterns: impl IntoIterator<Item = (P, V)>) -> Self {
+        let mut next = vec![NONE; 256];
+        let mut outputs = vec![None];
+        for (pattern, value) in patterns {
+            let pattern = pattern.as_ref();
+            if pattern.is_empty() {
+                continue;
+            }
+            let mut state = 0;
+            for &byte in pattern {
+                let edge = state * 256 + byte as usize;
+                if next[edge] == NONE {
+                    next[edge] = outputs.len() as u32;
+                    next.extend([NONE; 256]);
+                    outputs.push(None);
+                }
+                state = next[edge] as usize;
+            }
+            outputs[state] = Some((pattern.len(), value));
+        }
+
+        // Breadth first, so every state's failure target is finished before the state itself.
+        let mut failures = vec![0; outputs.len()];
+        let mut output_links = vec![NONE; outputs.len()];
+        let mut queue = VecDeque::new();
+        for edge in &mut next[..256] {
+            match *edge {
+                NONE => *edge = 0,
+                child => queue.push_back(child as usize),
+            }
+        }
+        while let Some(state) = queue.pop_front() {
+            for byte in 0..256 {
+                let edge = state * 256 + byte;
+                let fallback = next[failures[state] * 256 + byte];
+                match next[edge] {
+                    NONE => next[edge] = fallback,
+                    child => {
+                        let child = child as usize;
+                        failures[child] = fallback as usize;
+                        output_links[child] = if outputs[fallback as usize].is_some() {
+                            fallback
+                        } else {
+                            output_links[fallback as usize]
+                        };
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+
+        Self {
+            next,
+            outputs,
+            output_links,
+        }
+    }
+
+    /// Every match in `haystack`, overlapping ones included, in the order they end.
+    ///
+    /// Each match is `(start, end, value)`, counting positions by how many bytes the iterator had
+    /// produced, so for a reversed haystack they are offsets from its end.
+    pub fn matches<I: IntoIterator<Item = u8>>(&self, haystack: I) -> Matches<'_, I::IntoIter, V> {
+        Matches {
+            matcher: self,
+            haystack: haystack.into_iter(),
+            position: 0,
+            state: 0,
+            pending: NONE,
+        }
+    }
+
+    /// The first match to end in `haystack`.
+    pub fn find<I: IntoIterator<Item = u8>>(&self, haystack: I) -> Option<(usize, usize, V)> {
+        self.matches(haystack).next()
+    }
+}
+
+pub struct Matches<'a, I, V> {
+    matcher: &'a Matcher<V>,
+    haystack: I,
+    position: usize,
+    state: usize,
+    /// A state whose pattern ends here and hasn't been reported yet.
+    pending: u32,
+}
+
+impl<I: Iterator<Item = u8>, V: Copy> Iterator for Matches<'_, I, V> {
+    type Item = (usize, usize, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending == NONE {
+            let byte = self.haystack.next()?;
+            self.state = self.matcher.next[self.state * 256 + byte as usize] as usize;
+            self.position += 1;
+            self.pending = if self.matcher.outputs[self.state].is_some() {
+                self.state as u32
+            } else {
+                self.matcher.output_links[self.state]
+            };
+        }
+        let state = self.pending as usize;
+        self.pending = self.matcher.output_links[state];
+        let (len, value) = self.matcher.outputs[state]?;
+        Some((self.position - len, self.position, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_matches() {
+        let matcher = Matcher::new([("he", 0), ("she", 1), ("his", 2), ("hers", 3)]);
+        assert_eq!(
+            vec![(1, 4, 1), (2, 4, 0), (2, 6, 3)],
+            matcher.matches(*b"ushers").collect::<Vec<_>>()
+        );
+        assert_eq!(Some((0, 3, 2)), matcher.find(*b"hisher"));
+        assert_eq!(None, matcher.find(*b"hxs"));
+    }
+
+    #[test]
+    fn backwards() {
+        let words = ["one", "two", "eight"];
+        let reversed = Matcher::new(
+            words
+                .iter()
+                .enumerate()
+                .map(|(idx, word)| (word.bytes().rev().collect::<Vec<_>>(), idx)),
+        );
+        let haystack = b"eightwone";
+        assert_eq!(
+            Some((0, 3, 0)),
+            reversed.find(haystack.iter().rev().copied())
+        );
+        assert_eq!(
+            vec![(0, 3, 0), (2, 5, 1), (4, 9, 2)],
+            reversed
+                .matches(haystack.iter().rev().copied())
+                .collect::<Vec<_>>()
+        );
+    }
+}
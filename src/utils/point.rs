@@ -0,0 +1,206 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use super::direction::Direction;
+
+/// A point on the plane.
+///
+/// On a grid `x` is the column and `y` the row, so [`Direction::North`] decreases `y`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point2 {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Point2 {
+    pub const ORIGIN: Self = Self::new(0, 0);
+
+    pub const fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+
+    /// The point for a `(row, col)` grid index.
+    pub const fn from_index((row, col): (usize, usize)) -> Self {
+        Self::new(col as isize, row as isize)
+    }
+
+    /// The `(row, col)` grid index of this point, if it lies inside `shape`.
+    pub fn to_index(self, (rows, cols): (usize, usize)) -> Option<(usize, usize)> {
+        let row = usize::try_from(self.y).ok().filter(|&row| row < rows)?;
+        let col = usize::try_from(self.x).ok().filter(|&col| col < cols)?;
+        Some((row, col))
+    }
+
+    pub fn manhattan(self, other: Self) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    pub fn chebyshev(self, other: Self) -> usize {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// The point one step away in `direction`.
+    pub fn step(self, direction: Direction) -> Self {
+        self + direction
+    }
+
+    pub fn four_neighbors(self) -> impl Iterator<Item = Self> {
+        Direction::ALL.into_iter().map(move |d| self + d)
+    }
+
+    pub fn eight_neighbors(self) -> impl Iterator<Item = Self> {
+        itertools::iproduct!(-1..=1isize, -1..=1isize)
+            .filter(|&(x, y)| x != 0 || y != 0)
+            .map(move |(x, y)| self + Self::new(x, y))
+    }
+}
+
+impl From<(isize, isize)> for Point2 {
+    fn from((x, y): (isize, isize)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+/// A point in space.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point3 {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl Point3 {
+    pub const ORIGIN: Self = Self::new(0, 0, 0);
+
+    pub const fn new(x: isize, y: isize, z: isize) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn manhattan(self, other: Self) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+
+    pub fn chebyshev(self, other: Self) -> usize {
+        self.x
+            .abs_diff(other.x)
+            .max(self.y.abs_diff(other.y))
+            .max(self.z.abs_diff(other.z))
+    }
+
+    /// The points sharing a face with this one.
+    pub fn six_neighbors(self) -> impl Iterator<Item = Self> {
+        [
+            Self::new(-1, 0, 0),
+            Self::new(0, -1, 0),
+            Self::new(0, 0, -1),
+            Self::new(1, 0, 0),
+            Self::new(0, 1, 0),
+            Self::new(0, 0, 1),
+        ]
+        .into_iter()
+        .map(move |offset| self + offset)
+    }
+}
+
+impl From<[isize; 3]> for Point3 {
+    fn from([x, y, z]: [isize; 3]) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+impl From<Point3> for [isize; 3] {
+    fn from(p: Point3) -> Self {
+        [p.x, p.y, p.z]
+    }
+}
+
+macro_rules! arithmetic {
+    ($point:ident { $($field:ident),* }) => {
+        impl Add for $point {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self::Output {
+                Self { $($field: self.$field + rhs.$field),* }
+            }
+        }
+
+        impl Sub for $point {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                Self { $($field: self.$field - rhs.$field),* }
+            }
+        }
+
+        impl Mul<isize> for $point {
+            type Output = Self;
+
+            fn mul(self, rhs: isize) -> Self::Output {
+                Self { $($field: self.$field * rhs),* }
+            }
+        }
+
+        impl Neg for $point {
+            type Output = Self;
+
+            fn neg(self) -> Self::Output {
+                Self { $($field: -self.$field),* }
+            }
+        }
+
+        impl AddAssign for $point {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $point {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+    };
+}
+
+arithmetic!(Point2 { x, y });
+arithmetic!(Point3 { x, y, z });
+
+impl Add<Direction> for Point2 {
+    type Output = Self;
+
+    fn add(self, rhs: Direction) -> Self::Output {
+        self + rhs.offset()
+    }
+}
+
+impl AddAssign<Direction> for Point2 {
+    fn add_assign(&mut self, rhs: Direction) {
+        *self = *self + rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_and_distances() {
+        let a = Point2::new(1, -2);
+        let b = Point2::new(-3, 4);
+        assert_eq!(Point2::new(-2, 2), a + b);
+        assert_eq!(Point2::new(4, -6), a - b);
+        assert_eq!(Point2::new(3, -6), a * 3);
+        assert_eq!(10, a.manhattan(b));
+        assert_eq!(6, a.chebyshev(b));
+        assert_eq!(9, Point3::new(1, 2, 3).manhattan(Point3::new(-1, -1, -1)));
+    }
+
+    #[test]
+    fn grid_indices() {
+        let p = Point2::from_index((2, 5));
+        assert_eq!(Point2::new(5, 2), p);
+        assert_eq!(Some((2, 5)), p.to_index((3, 6)));
+        assert_eq!(None, p.to_index((2, 6)));
+        assert_eq!(None, (p + Direction::West * 6).to_index((3, 6)));
+        assert_eq!(Some((1, 5)), (p + Direction::North).to_index((3, 6)));
+    }
+}
@@ -0,0 +1,269 @@
+//! Graph searches over a successor closure.
+//!
+//! Nodes are anything `Eq + Hash + Clone`, so the same functions work on grid points, implicit
+//! state spaces and `petgraph` node indices alike: the closure decides what the edges are.
+
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use num::Zero;
+
+/// Walk `parents` back from `end` to the start of the search.
+fn build_path<N>(end: N, parents: &HashMap<N, N>) -> Vec<N>
+where
+    N: Eq + Hash + Clone,
+{
+    let mut path = vec![end];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Shortest path, by number of steps, from `start` to the first node satisfying `is_goal`.
+///
+/// The path includes both `start` and the goal, so its length is one more than the distance.
+pub fn bfs<N, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents = HashMap::new();
+    let mut seen = HashSet::from([start.clone()]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            return Some(build_path(node, &parents));
+        }
+        for next in successors(&node) {
+            if seen.insert(next.clone()) {
+                parents.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Number of steps from `start` to every node reachable from it.
+pub fn bfs_distances<N, I>(start: N, mut successors: impl FnMut(&N) -> I) -> HashMap<N, usize>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((node, distance)) = queue.pop_front() {
+        for next in successors(&node) {
+            if let Entry::Vacant(entry) = distances.entry(next.clone()) {
+                entry.insert(distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// Some path, not necessarily the shortest, from `start` to a node satisfying `is_goal`.
+pub fn dfs<N, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut stack = vec![start];
+    while let Some(node) = stack.pop() {
+        if !seen.insert(node.clone()) {
+            continue;
+        }
+        if is_goal(&node) {
+            return Some(build_path(node, &parents));
+        }
+        for next in successors(&node) {
+            if !seen.contains(&next) {
+                parents.insert(next.clone(), node.clone());
+                stack.push(next);
+            }
+        }
+    }
+    None
+}
+
+/// Every node reachable from `start`, including `start` itself.
+pub fn flood_fill<N, I>(start: N, successors: impl FnMut(&N) -> I) -> HashSet<N>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    bfs_distances(start, successors).into_keys().collect()
+}
+
+/// Cheapest path from `start` to a node satisfying `is_goal`, along with its cost.
+///
+/// `successors` yields each neighbor together with the cost of the edge to it.
+pub fn dijkstra<N, C, I>(
+    start: N,
+    successors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone + Ord,
+    C: Zero + Ord + Copy,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(start, successors, |_| C::zero(), is_goal)
+}
+
+/// Cost of the cheapest path from `start` to every node reachable from it.
+pub fn dijkstra_all<N, C, I>(start: N, mut successors: impl FnMut(&N) -> I) -> HashMap<N, C>
+where
+    N: Eq + Hash + Clone + Ord,
+    C: Zero + Ord + Copy,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut costs = HashMap::from([(start.clone(), C::zero())]);
+    let mut heap = BinaryHeap::from([Reverse((C::zero(), start))]);
+    while let Some(Reverse((cost, node))) = heap.pop() {
+        if costs.get(&node).is_some_and(|&best| best < cost) {
+            continue;
+        }
+        for (next, step) in successors(&node) {
+            let cost = cost + step;
+            if costs.get(&next).is_none_or(|&best| cost < best) {
+                costs.insert(next.clone(), cost);
+                heap.push(Reverse((cost, next)));
+            }
+        }
+    }
+    costs
+}
+
+/// Cheapest path from `start` to a node satisfying `is_goal`, guided by `heuristic`.
+///
+/// The heuristic must never overestimate the remaining cost, or the path may not be optimal.
+pub fn astar<N, C, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone + Ord,
+    C: Zero + Ord + Copy,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut parents = HashMap::new();
+    let mut costs = HashMap::from([(start.clone(), C::zero())]);
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&start), C::zero(), start))]);
+    while let Some(Reverse((_, cost, node))) = heap.pop() {
+        if is_goal(&node) {
+            return Some((build_path(node, &parents), cost));
+        }
+        if costs.get(&node).is_some_and(|&best| best < cost) {
+            continue;
+        }
+        for (next, step) in successors(&node) {
+            let cost = cost + step;
+            if costs.get(&next).is_none_or(|&best| cost < best) {
+                costs.insert(next.clone(), cost);
+                parents.insert(next.clone(), node.clone());
+                heap.push(Reverse((cost + heuristic(&next), cost, next)));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::{graph::DiGraph, visit::EdgeRef};
+
+    use super::*;
+    use crate::utils::{grid::Grid, point::Point2};
+
+    fn maze() -> Grid<char> {
+        Grid::from_chars(
+            "\
+            S.#.....\n\
+            .##.###.\n\
+            ....#..E\n\
+            ",
+        )
+        .unwrap()
+    }
+
+    fn open(grid: &Grid<char>) -> impl FnMut(&Point2) -> Vec<Point2> + '_ {
+        |p| {
+            p.four_neighbors()
+                .filter(|&n| grid.get_point(n).is_some_and(|&c| c != '#'))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn unweighted_searches() {
+        let grid = maze();
+        let start = Point2::new(0, 0);
+        let end = Point2::new(7, 2);
+
+        let path = bfs(start, open(&grid), |&p| p == end).unwrap();
+        assert_eq!(13, path.len() - 1);
+        assert_eq!(Some(&13), bfs_distances(start, open(&grid)).get(&end));
+
+        let path = dfs(start, open(&grid), |&p| p == end).unwrap();
+        assert_eq!(start, path[0]);
+        assert_eq!(end, *path.last().unwrap());
+        assert!(path.windows(2).all(|w| w[0].manhattan(w[1]) == 1));
+
+        let walls = grid.iter().filter(|&&c| c == '#').count();
+        assert_eq!(24 - walls, flood_fill(start, open(&grid)).len());
+        assert_eq!(None, bfs(start, open(&grid), |&p| p == Point2::new(2, 0)));
+    }
+
+    #[test]
+    fn weighted_searches() {
+        let grid = maze();
+        let start = Point2::new(0, 0);
+        let end = Point2::new(7, 2);
+        let mut weighted = |p: &Point2| {
+            let mut open = open(&grid);
+            open(p).into_iter().map(|n| (n, 1usize)).collect::<Vec<_>>()
+        };
+
+        let (path, cost) = dijkstra(start, &mut weighted, |&p| p == end).unwrap();
+        assert_eq!(13, cost);
+        assert_eq!(14, path.len());
+        let (_, cost) = astar(start, &mut weighted, |p| p.manhattan(end), |&p| p == end).unwrap();
+        assert_eq!(13, cost);
+        assert_eq!(Some(&13), dijkstra_all(start, &mut weighted).get(&end));
+    }
+
+    #[test]
+    fn petgraph_graphs() {
+        let mut graph = DiGraph::<(), u32>::new();
+        let [a, b, c, d] = [(); 4].map(|_| graph.add_node(()));
+        graph.extend_with_edges([(a, b, 7), (a, c, 2), (c, b, 3), (b, d, 1), (c, d, 9)]);
+
+        let (path, cost) = dijkstra(
+            a,
+            |&n| graph.edges(n).map(|e| (e.target(), *e.weight())),
+            |&n| n == d,
+        )
+        .unwrap();
+        assert_eq!(vec![a, c, b, d], path);
+        assert_eq!(6, cost);
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    fmt::Debug,
+    ops::{BitAnd, BitOr, Sub},
+};
+
+/// A set of integers below [`SmallSet::CAPACITY`], stored as the bits of a `u128`.
+///
+/// Unions and intersections are single instructions and the set is `Copy`, so it is cheap to keep
+/// one per line of input.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SmallSet(u128);
+
+impl SmallSet {
+    pub const CAPACITY: usize = u128::BITS as usize;
+
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Add `value`, returning whether it was new.
+    ///
+    /// Panics if `value` is not below [`SmallSet::CAPACITY`].
+    pub fn insert(&mut self, value: usize) -> bool {
+        assert!(value < Self::CAPACITY, "{value} doesn't fit in a SmallSet");
+        let new = !self.contains(value);
+        self.0 |= 1 << value;
+        new
+    }
+
+    /// Take `value` out, returning whether it was there.
+    pub fn remove(&mut self, value: usize) -> bool {
+        let present = self.contains(value);
+        if present {
+            self.0 &= !(1 << value);
+        }
+        present
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        value < Self::CAPACITY && self.0 & 1 << value != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Everything in `self` that is not in `other`.
+    pub fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// The values in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> {
+        let mut bits = self.0;
+        std::iter::from_fn(move || {
+            let value = bits.trailing_zeros() as usize;
+            (bits != 0).then(|| {
+                bits &= bits - 1;
+                value
+            })
+        })
+    }
+}
+
+impl Debug for SmallSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<usize> for SmallSet {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|value| {
+            set.insert(value);
+        });
+        set
+    }
+}
+
+impl BitAnd for SmallSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersection(rhs)
+    }
+}
+
+impl BitOr for SmallSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl Sub for SmallSet {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.difference(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_operations() {
+        let a = [1, 5, 64, 127].into_iter().collect::<SmallSet>();
+        let b = [0, 5, 127].into_iter().collect::<SmallSet>();
+        assert_eq!(vec![5, 127], (a & b).iter().collect::<Vec<_>>());
+        assert_eq!(vec![0, 1, 5, 64, 127], (a | b).iter().collect::<Vec<_>>());
+        assert_eq!(vec![1, 64], (a - b).iter().collect::<Vec<_>>());
+        assert_eq!(4, a.len());
+        assert!(a.contains(64));
+        assert!(!a.contains(200));
+        assert_eq!("{1, 5, 64, 127}", format!("{a:?}"));
+
+        let mut c = SmallSet::new();
+        assert!(c.insert(3));
+        assert!(!c.insert(3));
+        assert!(c.remove(3));
+        assert!(!c.remove(3));
+        assert!(c.is_empty());
+    }
+}